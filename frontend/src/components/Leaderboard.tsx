@@ -9,6 +9,9 @@ interface Player {
   pug_wins: number;
   pug_losses: number;
   pug_draws: number;
+  position: number;
+  rank_tier: string;
+  games_played: number;
 }
 
 const Leaderboard: React.FC = () => {
@@ -39,6 +42,7 @@ const Leaderboard: React.FC = () => {
             <tr>
               <th className="px-6 py-3 text-left">Rank</th>
               <th className="px-6 py-3 text-left">Player</th>
+              <th className="px-6 py-3 text-left">Tier</th>
               <th className="px-6 py-3 text-right">ELO</th>
               <th className="px-6 py-3 text-right">W</th>
               <th className="px-6 py-3 text-right">L</th>
@@ -47,9 +51,9 @@ const Leaderboard: React.FC = () => {
             </tr>
           </thead>
           <tbody className="divide-y divide-gray-700">
-            {players.map((player, index) => (
+            {players.map(player => (
               <tr key={player.id} className="bg-gray-800 hover:bg-gray-700 text-gray-200">
-                <td className="px-6 py-4">{index + 1}</td>
+                <td className="px-6 py-4">{player.position}</td>
                 <td className="px-6 py-4">
                   <Link 
                     to={`/player/${player.player_name}`}
@@ -58,6 +62,7 @@ const Leaderboard: React.FC = () => {
                     {player.player_name}
                   </Link>
                 </td>
+                <td className="px-6 py-4">{player.rank_tier}</td>
                 <td className="px-6 py-4 text-right">{player.current_elo}</td>
                 <td className="px-6 py-4 text-right text-green-400">{player.pug_wins}</td>
                 <td className="px-6 py-4 text-right text-red-400">{player.pug_losses}</td>
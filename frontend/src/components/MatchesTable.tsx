@@ -8,6 +8,7 @@ interface Player {
   discord_id: string;
   player_name: string;
   current_elo: number;
+  match_elo: number | null;
 }
 
 interface Match {
@@ -20,6 +21,7 @@ interface Match {
     losing_score: number | null;
     map: string | null;
     server: string | null;
+    game_type: string | null;
     match_outcome: number | null;
     stats_url: string | null;
     created_at: string;
@@ -28,15 +30,38 @@ interface Match {
   red_team_players: Player[];
 }
 
+interface MatchPage {
+  items: Match[];
+  total: number;
+  limit: number;
+  next_cursor: number | null;
+}
+
 type SortKey = 'created_at' | 'map';
 type SortOrder = 'asc' | 'desc';
+const PAGE_SIZE = 50;
+// The table lists pugs unless another game type is picked
+const DEFAULT_GAME_TYPE = '4v4';
+
+// Filter options seen so far, so a selected map or server stays listed when
+// the current page no longer contains it
+const mergeOptions = (known: string[], values: (string | null)[]) =>
+  Array.from(new Set([...known, ...values.filter((value): value is string => Boolean(value))])).sort();
+
 const MatchesTable: React.FC = () => {
   const [matches, setMatches] = useState<Match[]>([]);
-  const [filteredMatches, setFilteredMatches] = useState<Match[]>([]);
+  const [total, setTotal] = useState(0);
+  const [cursor, setCursor] = useState(0);
+  const [nextCursor, setNextCursor] = useState<number | null>(null);
+  const [knownMaps, setKnownMaps] = useState<string[]>([]);
+  const [knownServers, setKnownServers] = useState<string[]>([]);
+  const [knownGameTypes, setKnownGameTypes] = useState<string[]>([DEFAULT_GAME_TYPE]);
+  const [playerFilter, setPlayerFilter] = useState<string>('');
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [mapFilter, setMapFilter] = useState<string>('');
   const [serverFilter, setServerFilter] = useState<string>('');
+  const [gameTypeFilter, setGameTypeFilter] = useState<string>(DEFAULT_GAME_TYPE);
   const [startDate, setStartDate] = useState<string>('');
   const [endDate, setEndDate] = useState<string>('');
   const [sortKey, setSortKey] = useState<SortKey>('created_at');
@@ -83,19 +108,47 @@ const MatchesTable: React.FC = () => {
     navigate(`/player/${encodeURIComponent(playerName)}`);
   };
 
+  // Filter by player once typing pauses, rather than on every keystroke
+  useEffect(() => {
+    const timeout = setTimeout(() => setPlayerFilter(playerSearch.trim()), 300);
+    return () => clearTimeout(timeout);
+  }, [playerSearch]);
+
+  // Any change to the filters or sorting starts again from the first page
+  useEffect(() => {
+    setCursor(0);
+  }, [mapFilter, serverFilter, gameTypeFilter, startDate, endDate, sortKey, sortOrder, playerFilter]);
+
   useEffect(() => {
     const fetchData = async () => {
       setLoading(true);
       try {
-        const matchesResponse = await fetch('/api/matches/with-players');
+        const params = new URLSearchParams({
+          sort: sortKey,
+          order: sortOrder,
+          limit: String(PAGE_SIZE),
+          cursor: String(cursor),
+        });
+        if (mapFilter) params.set('map', mapFilter);
+        if (serverFilter) params.set('server', serverFilter);
+        if (gameTypeFilter) params.set('game_type', gameTypeFilter);
+        if (startDate) params.set('from', startDate);
+        if (endDate) params.set('to', endDate);
+        if (playerFilter) params.set('player', playerFilter);
+
+        const matchesResponse = await fetch(`/api/matches/with-players?${params}`);
 
         if (!matchesResponse.ok) {
           throw new Error('Failed to fetch data');
         }
 
-        const matchesData = await matchesResponse.json();
-        setMatches(matchesData);
-        setFilteredMatches(matchesData);
+        const page: MatchPage = await matchesResponse.json();
+        setMatches(page.items);
+        setTotal(page.total);
+        setNextCursor(page.next_cursor);
+        setKnownMaps(prev => mergeOptions(prev, page.items.map(match => match.match_data.map)));
+        setKnownServers(prev => mergeOptions(prev, page.items.map(match => match.match_data.server)));
+        setKnownGameTypes(prev => mergeOptions(prev, page.items.map(match => match.match_data.game_type)));
         setLoading(false);
       } catch (error) {
         console.error('Error fetching data:', error);
@@ -105,38 +158,7 @@ const MatchesTable: React.FC = () => {
     };
 
     fetchData();
-  }, []);
-
-  useEffect(() => {
-    let filtered = matches.filter(match => {
-      const matchDate = new Date(match.match_data.created_at);
-      const matchPlayers = [...match.blue_team_players, ...match.red_team_players];
-      return (
-        (!mapFilter || match.match_data.map === mapFilter) &&
-        (!serverFilter || match.match_data.server === serverFilter) &&
-        (!startDate || matchDate >= new Date(startDate)) &&
-        (!endDate || matchDate <= new Date(endDate)) &&
-        (!playerSearch || matchPlayers.some(player => 
-          player.player_name.toLowerCase().includes(playerSearch.toLowerCase())
-        ))
-      );
-    });
-
-    filtered.sort((a, b) => {
-      if (sortKey === 'created_at') {
-        return sortOrder === 'asc' 
-          ? new Date(a.match_data.created_at).getTime() - new Date(b.match_data.created_at).getTime()
-          : new Date(b.match_data.created_at).getTime() - new Date(a.match_data.created_at).getTime();
-      } else if (sortKey === 'map') {
-        return sortOrder === 'asc'
-          ? (a.match_data.map || '').localeCompare(b.match_data.map || '')
-          : (b.match_data.map || '').localeCompare(a.match_data.map || '');
-      }
-      return 0;
-    });
-
-    setFilteredMatches(filtered);
-  }, [matches, mapFilter, serverFilter, startDate, endDate, sortKey, sortOrder, playerSearch]);
+  }, [cursor, mapFilter, serverFilter, gameTypeFilter, startDate, endDate, sortKey, sortOrder, playerFilter]);
 
   const handleSort = (key: SortKey) => {
     if (sortKey === key) {
@@ -175,14 +197,11 @@ const MatchesTable: React.FC = () => {
     return { blueScore, redScore };
   };
   
-  const uniqueMaps = Array.from(new Set(matches.map(match => match.match_data.map).filter(Boolean)));
-  const uniqueServers = Array.from(new Set(matches.map(match => match.match_data.server).filter(Boolean)));
-
   const downloadCSV = () => {
     const headers = ['Match ID', 'Date Played', 'Map', 'Server', 'Blue Team', 'Red Team', 'Blue Score', 'Red Score', 'Outcome'];
     const csvContent = [
       headers.join(','),
-      ...filteredMatches.map(match => {
+      ...matches.map(match => {
         const { blueScore, redScore } = getScores(match.match_data);
         return [
           match.match_data.match_id,
@@ -224,23 +243,29 @@ const MatchesTable: React.FC = () => {
     };
   }, []);
 
-  if (loading) return <p className="loading">Loading matches...</p>;
+  if (loading && matches.length === 0) return <p className="loading">Loading matches...</p>;
   if (error) return <p className="error">{error}</p>;
 
   return (
     <div className="matches-container">
-      <h3 className="matches-title">4v4 Matches</h3>
+      <h3 className="matches-title">{gameTypeFilter ? `${gameTypeFilter} Matches` : 'All Matches'}</h3>
       <div className="filters">
         <div className="filter-group">
+          <select value={gameTypeFilter} onChange={(e) => setGameTypeFilter(e.target.value)}>
+            <option value="">All Game Types</option>
+            {knownGameTypes.map(gameType => (
+              <option key={gameType} value={gameType}>{gameType}</option>
+            ))}
+          </select>
           <select value={mapFilter} onChange={(e) => setMapFilter(e.target.value)}>
             <option value="">All Maps</option>
-            {uniqueMaps.map(map => (
+            {knownMaps.map(map => (
               <option key={map} value={map}>{map}</option>
             ))}
           </select>
           <select value={serverFilter} onChange={(e) => setServerFilter(e.target.value)}>
             <option value="">All Servers</option>
-            {uniqueServers.map(server => (
+            {knownServers.map(server => (
               <option key={server} value={server}>{server}</option>
             ))}
           </select>
@@ -303,7 +328,7 @@ const MatchesTable: React.FC = () => {
             </tr>
           </thead>
           <tbody>
-            {filteredMatches.map(match => {
+            {matches.map(match => {
               const { blueScore, redScore } = getScores(match.match_data);
               return (
                 <tr key={match.match_data.id} className={getOutcomeClass(match.match_data.match_outcome)}>
@@ -326,6 +351,7 @@ const MatchesTable: React.FC = () => {
                           <span 
                             key={index} 
                             className="player-name clickable" 
+                            title={player.match_elo !== null ? `Elo ${player.match_elo}` : undefined}
                             onClick={() => handlePlayerClick(player.player_name)}
                           >
                             {player.player_name}
@@ -338,6 +364,7 @@ const MatchesTable: React.FC = () => {
                           <span 
                             key={index} 
                             className="player-name clickable" 
+                            title={player.match_elo !== null ? `Elo ${player.match_elo}` : undefined}
                             onClick={() => handlePlayerClick(player.player_name)}
                           >
                             {player.player_name}
@@ -360,6 +387,17 @@ const MatchesTable: React.FC = () => {
           </tbody>
         </table>
       </div>
+      <div className="pagination">
+        <button onClick={() => setCursor(Math.max(0, cursor - PAGE_SIZE))} disabled={cursor === 0}>
+          Previous
+        </button>
+        <span>
+          {total === 0 ? 0 : cursor + 1}–{cursor + matches.length} of {total}
+        </span>
+        <button onClick={() => nextCursor !== null && setCursor(nextCursor)} disabled={nextCursor === null}>
+          Next
+        </button>
+      </div>
     </div>
   );
 };
@@ -10,6 +10,19 @@ mod m20240905_040043_update_matches_table_scores_stats;
 mod m20240906_042943_add_players_table;
 mod m20241004_162642_player_elos;
 mod m20241007_163422_rename_player_elos_to_player_elo;
+mod m20241020_174205_signed_player_and_match_ids;
+mod m20241021_193512_add_match_participants;
+mod m20241023_181204_align_player_elo_columns;
+mod m20241027_141830_add_role_to_users;
+mod m20241104_190215_add_seasons;
+mod m20241110_153204_add_achievements;
+mod m20241114_172315_add_player_penalties;
+mod m20241118_203140_add_match_player_stats;
+mod m20241122_101522_add_stats_snapshots;
+mod m20241126_184410_add_player_aliases;
+mod m20241130_092717_add_unique_match_id;
+mod m20241130_104152_drop_players_achievements;
+mod m20241130_121530_add_match_player_stats_source;
 pub struct Migrator;
 
 #[async_trait::async_trait]
@@ -23,6 +36,19 @@ impl MigratorTrait for Migrator {
             Box::new(m20240906_042943_add_players_table::Migration),
             Box::new(m20241004_162642_player_elos::Migration),
             Box::new(m20241007_163422_rename_player_elos_to_player_elo::Migration),
+            Box::new(m20241020_174205_signed_player_and_match_ids::Migration),
+            Box::new(m20241021_193512_add_match_participants::Migration),
+            Box::new(m20241023_181204_align_player_elo_columns::Migration),
+            Box::new(m20241027_141830_add_role_to_users::Migration),
+            Box::new(m20241104_190215_add_seasons::Migration),
+            Box::new(m20241110_153204_add_achievements::Migration),
+            Box::new(m20241114_172315_add_player_penalties::Migration),
+            Box::new(m20241118_203140_add_match_player_stats::Migration),
+            Box::new(m20241122_101522_add_stats_snapshots::Migration),
+            Box::new(m20241126_184410_add_player_aliases::Migration),
+            Box::new(m20241130_092717_add_unique_match_id::Migration),
+            Box::new(m20241130_104152_drop_players_achievements::Migration),
+            Box::new(m20241130_121530_add_match_player_stats_source::Migration),
         ]
     }
 }
\ No newline at end of file
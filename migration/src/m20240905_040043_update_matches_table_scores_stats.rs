@@ -6,29 +6,36 @@ pub struct Migration;
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        manager
-            .alter_table(
-                Table::alter()
-                    .table(Matches::Table)
-                    .add_column(ColumnDef::new(Matches::WinningScore).integer().null())
-                    .add_column(ColumnDef::new(Matches::LosingScore).integer().null())
-                    .add_column(ColumnDef::new(Matches::StatsUrl).text().null())
-                    .to_owned(),
-            )
-            .await
+        // One column per statement, since SQLite takes a single alter option
+        for column in [
+            ColumnDef::new(Matches::WinningScore).integer().null().to_owned(),
+            ColumnDef::new(Matches::LosingScore).integer().null().to_owned(),
+            ColumnDef::new(Matches::StatsUrl).text().null().to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Matches::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        manager
-            .alter_table(
-                Table::alter()
-                    .table(Matches::Table)
-                    .drop_column(Matches::WinningScore)
-                    .drop_column(Matches::LosingScore)
-                    .drop_column(Matches::StatsUrl)
-                    .to_owned(),
-            )
-            .await
+        for column in [Matches::WinningScore, Matches::LosingScore, Matches::StatsUrl] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Matches::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
     }
 }
 
@@ -29,6 +29,7 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
 enum PlayerElos {
     Table,
     Id,
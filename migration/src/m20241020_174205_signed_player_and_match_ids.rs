@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The bot created `players.id` and `matches.id` as `INT UNSIGNED` in MySQL,
+/// while every other backend has signed keys. Makes them signed there too, so
+/// the entities and the key columns of later tables can use `i32` everywhere.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::MySql {
+            return Ok(());
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Players::Table)
+                    .modify_column(ColumnDef::new(Players::Id).integer().not_null().auto_increment())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Matches::Table)
+                    .modify_column(ColumnDef::new(Matches::Id).integer().not_null().auto_increment())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::MySql {
+            return Ok(());
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Players::Table)
+                    .modify_column(ColumnDef::new(Players::Id).unsigned().not_null().auto_increment())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Matches::Table)
+                    .modify_column(ColumnDef::new(Matches::Id).unsigned().not_null().auto_increment())
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Players {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Matches {
+    Table,
+    Id,
+}
@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{
+    prelude::*,
+    schema::*,
+    sea_orm::{FromQueryResult, JsonValue},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(MatchParticipants::Table)
+            .col(pk_auto(MatchParticipants::Id))
+            .col(integer(MatchParticipants::MatchId))
+            .col(integer(MatchParticipants::PlayerId))
+            .col(string(MatchParticipants::Team))
+            .col(integer(MatchParticipants::Slot))
+            .to_owned();
+        manager.create_table(table).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-match_participants-match_id-player_id")
+                    .table(MatchParticipants::Table)
+                    .col(MatchParticipants::MatchId)
+                    .col(MatchParticipants::PlayerId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-match_participants-player_id")
+                    .table(MatchParticipants::Table)
+                    .col(MatchParticipants::PlayerId)
+                    .to_owned(),
+            )
+            .await?;
+
+        backfill(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MatchParticipants::Table).to_owned())
+            .await
+    }
+}
+
+/// Copies the comma-joined Discord IDs in `matches.blue_team`/`red_team` into
+/// participant rows. IDs without a matching player are skipped, the same way
+/// the roster endpoints always dropped them.
+async fn backfill(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = manager.get_database_backend();
+
+    let players = Query::select()
+        .columns([Players::Id, Players::DiscordId])
+        .from(Players::Table)
+        .and_where(Expr::col(Players::DiscordId).is_not_null())
+        .to_owned();
+    let players = JsonValue::find_by_statement(backend.build(&players))
+        .all(db)
+        .await?;
+    let player_ids: HashMap<String, u64> = players
+        .iter()
+        .filter_map(|row| {
+            let discord_id = row["discord_id"].as_str()?.trim().to_string();
+            Some((discord_id, row["id"].as_u64()?))
+        })
+        .collect();
+
+    let matches = Query::select()
+        .columns([Matches::Id, Matches::BlueTeam, Matches::RedTeam])
+        .from(Matches::Table)
+        .to_owned();
+    let matches = JsonValue::find_by_statement(backend.build(&matches))
+        .all(db)
+        .await?;
+
+    for row in matches {
+        let Some(match_id) = row["id"].as_u64() else {
+            continue;
+        };
+
+        let mut insert = Query::insert()
+            .into_table(MatchParticipants::Table)
+            .columns([
+                MatchParticipants::MatchId,
+                MatchParticipants::PlayerId,
+                MatchParticipants::Team,
+                MatchParticipants::Slot,
+            ])
+            .to_owned();
+        let mut seen = HashSet::new();
+        for (team, column) in [("blue", "blue_team"), ("red", "red_team")] {
+            let roster = row[column].as_str().unwrap_or_default();
+            let ids = roster.split(',').map(str::trim).filter(|id| !id.is_empty());
+            for (slot, discord_id) in ids.enumerate() {
+                let Some(&player_id) = player_ids.get(discord_id) else {
+                    continue;
+                };
+                if !seen.insert(player_id) {
+                    continue;
+                }
+                insert.values_panic([
+                    match_id.into(),
+                    player_id.into(),
+                    team.into(),
+                    <i32 as TryFrom<usize>>::try_from(slot).unwrap_or(i32::MAX).into(),
+                ]);
+            }
+        }
+
+        if !seen.is_empty() {
+            db.execute(backend.build(&insert)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(DeriveIden)]
+enum MatchParticipants {
+    Table,
+    Id,
+    MatchId,
+    PlayerId,
+    Team,
+    Slot,
+}
+
+#[derive(DeriveIden)]
+enum Matches {
+    Table,
+    Id,
+    BlueTeam,
+    RedTeam,
+}
+
+#[derive(DeriveIden)]
+enum Players {
+    Table,
+    Id,
+    DiscordId,
+}
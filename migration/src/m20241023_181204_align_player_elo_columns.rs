@@ -0,0 +1,156 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The bot owns `player_elo` in production and created it with an `entryID`
+/// key and nullable columns, which is what the entity maps. Databases built
+/// only from our migrations got the older `player_elos` layout instead, which
+/// is told apart by its `entry_id` column. There the rows are copied into a
+/// table with the bot's layout, which then takes the place of the old one.
+/// Tables without `entry_id` are left alone.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("player_elo", "entry_id").await? {
+            return Ok(());
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Aligned::Table)
+                    .col(pk_auto(PlayerElo::EntryId))
+                    .col(big_integer_null(PlayerElo::MatchId))
+                    .col(string_null(PlayerElo::PlayerName))
+                    .col(integer_null(PlayerElo::PlayerElos))
+                    .col(big_integer_null(PlayerElo::DiscordId))
+                    .col(
+                        timestamp_with_time_zone_null(PlayerElo::CreatedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // New keys are assigned in the order of the old ones
+        let columns = [
+            PlayerElo::MatchId,
+            PlayerElo::PlayerName,
+            PlayerElo::PlayerElos,
+            PlayerElo::DiscordId,
+            PlayerElo::CreatedAt,
+        ];
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Aligned::Table)
+                    .columns(columns)
+                    .select_from(
+                        Query::select()
+                            .columns(columns)
+                            .from(PlayerElo::Table)
+                            .order_by(Legacy::Id, Order::Asc)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Custom(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        replace(manager, Aligned::Table).await
+    }
+
+    /// Puts the rows back into the `player_elos` layout. Values the old
+    /// layout requires but the bot's leaves empty become 0, `''` or the
+    /// current time.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.has_column("player_elo", "entry_id").await? {
+            return Ok(());
+        }
+
+        manager
+            .create_table(
+                table_auto_tz(Aligned::Table)
+                    .col(pk_auto(Legacy::Id))
+                    .col(integer(Legacy::EntryId))
+                    .col(integer(PlayerElo::MatchId))
+                    .col(string(PlayerElo::PlayerName))
+                    .col(integer(PlayerElo::PlayerElos))
+                    .col(big_integer(PlayerElo::DiscordId))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Aligned::Table)
+                    .columns([
+                        Legacy::EntryId.into_iden(),
+                        PlayerElo::MatchId.into_iden(),
+                        PlayerElo::PlayerName.into_iden(),
+                        PlayerElo::PlayerElos.into_iden(),
+                        PlayerElo::DiscordId.into_iden(),
+                        PlayerElo::CreatedAt.into_iden(),
+                        Legacy::UpdatedAt.into_iden(),
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(PlayerElo::EntryId)
+                            .expr(Expr::col(PlayerElo::MatchId).if_null(0))
+                            .expr(Expr::col(PlayerElo::PlayerName).if_null(""))
+                            .expr(Expr::col(PlayerElo::PlayerElos).if_null(0))
+                            .expr(Expr::col(PlayerElo::DiscordId).if_null(0))
+                            .expr(Expr::col(PlayerElo::CreatedAt).if_null(Expr::current_timestamp()))
+                            .expr(Expr::col(PlayerElo::CreatedAt).if_null(Expr::current_timestamp()))
+                            .from(PlayerElo::Table)
+                            .order_by(PlayerElo::EntryId, Order::Asc)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Custom(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        replace(manager, Aligned::Table).await
+    }
+}
+
+/// Drops `player_elo` and renames `table` to take its place.
+async fn replace(manager: &SchemaManager<'_>, table: Aligned) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(PlayerElo::Table).to_owned())
+        .await?;
+    manager
+        .rename_table(Table::rename().table(table, PlayerElo::Table).to_owned())
+        .await
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum PlayerElo {
+    Table,
+    #[sea_orm(iden = "entryID")]
+    EntryId,
+    MatchId,
+    PlayerName,
+    PlayerElos,
+    DiscordId,
+    CreatedAt,
+}
+
+/// Columns only the `player_elos` layout has.
+#[derive(DeriveIden)]
+enum Legacy {
+    Id,
+    EntryId,
+    UpdatedAt,
+}
+
+/// The table rows are copied into while the layout changes.
+#[derive(DeriveIden, Clone, Copy)]
+enum Aligned {
+    #[sea_orm(iden = "player_elo_aligned")]
+    Table,
+}
@@ -0,0 +1,86 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(Seasons::Table)
+            .col(pk_auto(Seasons::Id))
+            .col(string_uniq(Seasons::Name))
+            .col(timestamp_with_time_zone(Seasons::StartsAt))
+            .col(timestamp_with_time_zone_null(Seasons::EndsAt))
+            .col(integer(Seasons::StartingRating).default(1000))
+            .col(timestamp_with_time_zone_null(Seasons::ArchivedAt))
+            .to_owned();
+        manager.create_table(table).await?;
+
+        let table = table_auto_tz(SeasonStandings::Table)
+            .col(pk_auto(SeasonStandings::Id))
+            .col(integer(SeasonStandings::SeasonId))
+            .col(integer(SeasonStandings::PlayerId))
+            .col(integer(SeasonStandings::Position))
+            .col(integer(SeasonStandings::Rating))
+            .col(integer(SeasonStandings::GamesPlayed))
+            .col(integer(SeasonStandings::Wins))
+            .col(integer(SeasonStandings::Losses))
+            .col(integer(SeasonStandings::Draws))
+            .foreign_key(
+                ForeignKey::create()
+                    .name("fk-season_standings-season_id")
+                    .from(SeasonStandings::Table, SeasonStandings::SeasonId)
+                    .to(Seasons::Table, Seasons::Id)
+                    .on_delete(ForeignKeyAction::Cascade),
+            )
+            .to_owned();
+        manager.create_table(table).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-season_standings-season_id-player_id")
+                    .table(SeasonStandings::Table)
+                    .col(SeasonStandings::SeasonId)
+                    .col(SeasonStandings::PlayerId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SeasonStandings::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Seasons::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Seasons {
+    Table,
+    Id,
+    Name,
+    StartsAt,
+    EndsAt,
+    StartingRating,
+    ArchivedAt,
+}
+
+#[derive(DeriveIden)]
+enum SeasonStandings {
+    Table,
+    Id,
+    SeasonId,
+    PlayerId,
+    Position,
+    Rating,
+    GamesPlayed,
+    Wins,
+    Losses,
+    Draws,
+}
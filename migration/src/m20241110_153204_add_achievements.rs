@@ -0,0 +1,76 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(Achievements::Table)
+            .col(pk_auto(Achievements::Id))
+            .col(string_uniq(Achievements::Key))
+            .col(string(Achievements::Name))
+            .col(text(Achievements::Description))
+            .col(string_null(Achievements::Icon))
+            .to_owned();
+        manager.create_table(table).await?;
+
+        let table = table_auto_tz(PlayerAchievements::Table)
+            .col(pk_auto(PlayerAchievements::Id))
+            .col(integer(PlayerAchievements::PlayerId))
+            .col(integer(PlayerAchievements::AchievementId))
+            .col(integer_null(PlayerAchievements::MatchId))
+            .col(timestamp_with_time_zone(PlayerAchievements::UnlockedAt))
+            .foreign_key(
+                ForeignKey::create()
+                    .name("fk-player_achievements-achievement_id")
+                    .from(PlayerAchievements::Table, PlayerAchievements::AchievementId)
+                    .to(Achievements::Table, Achievements::Id)
+                    .on_delete(ForeignKeyAction::Cascade),
+            )
+            .to_owned();
+        manager.create_table(table).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-player_achievements-player_id-achievement_id")
+                    .table(PlayerAchievements::Table)
+                    .col(PlayerAchievements::PlayerId)
+                    .col(PlayerAchievements::AchievementId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PlayerAchievements::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Achievements::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Achievements {
+    Table,
+    Id,
+    Key,
+    Name,
+    Description,
+    Icon,
+}
+
+#[derive(DeriveIden)]
+enum PlayerAchievements {
+    Table,
+    Id,
+    PlayerId,
+    AchievementId,
+    MatchId,
+    UnlockedAt,
+}
@@ -0,0 +1,68 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(PlayerPenalties::Table)
+            .col(pk_auto(PlayerPenalties::Id))
+            .col(integer(PlayerPenalties::PlayerId))
+            .col(text(PlayerPenalties::Reason))
+            .col(integer_null(PlayerPenalties::IssuedBy))
+            .col(timestamp_with_time_zone(PlayerPenalties::IssuedAt))
+            .col(timestamp_with_time_zone_null(PlayerPenalties::ExpiresAt))
+            .col(integer_null(PlayerPenalties::MatchId))
+            .col(timestamp_with_time_zone_null(PlayerPenalties::RevokedAt))
+            .col(integer_null(PlayerPenalties::RevokedBy))
+            .col(timestamp_with_time_zone_null(PlayerPenalties::ExpiredAt))
+            .foreign_key(
+                ForeignKey::create()
+                    .name("fk-player_penalties-issued_by")
+                    .from(PlayerPenalties::Table, PlayerPenalties::IssuedBy)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::SetNull),
+            )
+            .to_owned();
+        manager.create_table(table).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-player_penalties-player_id")
+                    .table(PlayerPenalties::Table)
+                    .col(PlayerPenalties::PlayerId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PlayerPenalties::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlayerPenalties {
+    Table,
+    Id,
+    PlayerId,
+    Reason,
+    IssuedBy,
+    IssuedAt,
+    ExpiresAt,
+    MatchId,
+    RevokedAt,
+    RevokedBy,
+    ExpiredAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,66 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(MatchPlayerStats::Table)
+            .col(pk_auto(MatchPlayerStats::Id))
+            .col(integer(MatchPlayerStats::MatchId))
+            .col(integer(MatchPlayerStats::PlayerId))
+            .col(string_null(MatchPlayerStats::Team))
+            .col(integer(MatchPlayerStats::Kills).default(0))
+            .col(integer(MatchPlayerStats::Deaths).default(0))
+            .col(integer(MatchPlayerStats::Suicides).default(0))
+            .col(integer(MatchPlayerStats::Teamkills).default(0))
+            .col(integer(MatchPlayerStats::FlagCaps).default(0))
+            .col(integer(MatchPlayerStats::FlagTouches).default(0))
+            .to_owned();
+        manager.create_table(table).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-match_player_stats-match_id-player_id")
+                    .table(MatchPlayerStats::Table)
+                    .col(MatchPlayerStats::MatchId)
+                    .col(MatchPlayerStats::PlayerId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-match_player_stats-player_id")
+                    .table(MatchPlayerStats::Table)
+                    .col(MatchPlayerStats::PlayerId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MatchPlayerStats::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MatchPlayerStats {
+    Table,
+    Id,
+    MatchId,
+    PlayerId,
+    Team,
+    Kills,
+    Deaths,
+    Suicides,
+    Teamkills,
+    FlagCaps,
+    FlagTouches,
+}
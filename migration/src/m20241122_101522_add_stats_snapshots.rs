@@ -0,0 +1,47 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(StatsSnapshots::Table)
+            .col(pk_auto(StatsSnapshots::Id))
+            .col(integer_uniq(StatsSnapshots::MatchId))
+            .col(string(StatsSnapshots::Url))
+            .col(string(StatsSnapshots::Status))
+            .col(integer(StatsSnapshots::Attempts).default(0))
+            .col(integer_null(StatsSnapshots::HttpStatus))
+            .col(string_null(StatsSnapshots::ContentType))
+            .col(text_null(StatsSnapshots::Body))
+            .col(json_null(StatsSnapshots::Summary))
+            .col(text_null(StatsSnapshots::Error))
+            .col(timestamp_with_time_zone_null(StatsSnapshots::FetchedAt))
+            .to_owned();
+        manager.create_table(table).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StatsSnapshots::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StatsSnapshots {
+    Table,
+    Id,
+    MatchId,
+    Url,
+    Status,
+    Attempts,
+    HttpStatus,
+    ContentType,
+    Body,
+    Summary,
+    Error,
+    FetchedAt,
+}
@@ -0,0 +1,54 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let table = table_auto_tz(PlayerAliases::Table)
+            .col(pk_auto(PlayerAliases::Id))
+            .col(integer(PlayerAliases::PlayerId))
+            .col(string(PlayerAliases::Alias))
+            .col(timestamp_with_time_zone(PlayerAliases::LastUsedAt))
+            .to_owned();
+        manager.create_table(table).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-player_aliases-player_id-alias")
+                    .table(PlayerAliases::Table)
+                    .col(PlayerAliases::PlayerId)
+                    .col(PlayerAliases::Alias)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-player_aliases-alias")
+                    .table(PlayerAliases::Table)
+                    .col(PlayerAliases::Alias)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PlayerAliases::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlayerAliases {
+    Table,
+    Id,
+    PlayerId,
+    Alias,
+    LastUsedAt,
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{FromQueryResult, JsonValue},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Bot match numbers are allocated as the highest one plus one, so two
+/// reports at once could otherwise get the same number.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        release_duplicates(manager).await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-matches-match_id")
+                    .table(Matches::Table)
+                    .col(Matches::MatchId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-matches-match_id")
+                    .table(Matches::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Makes the existing match numbers unique. A number held by one live match
+/// and soft-deleted ones stays with the live match; soft-deleted holders give
+/// theirs up (`match_id` becomes null). Two live matches with one number
+/// cannot be settled here, so the migration stops and lists them.
+async fn release_duplicates(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = manager.get_database_backend();
+
+    let rows = Query::select()
+        .columns([Matches::Id, Matches::MatchId, Matches::DeletedAt])
+        .from(Matches::Table)
+        .and_where(Expr::col(Matches::MatchId).is_not_null())
+        .order_by(Matches::Id, Order::Asc)
+        .to_owned();
+    let rows = JsonValue::find_by_statement(backend.build(&rows))
+        .all(db)
+        .await?;
+
+    // match number -> (id, deleted) of every match holding it
+    let mut holders: BTreeMap<i64, Vec<(i64, bool)>> = BTreeMap::new();
+    for row in &rows {
+        let (Some(id), Some(number)) = (row["id"].as_i64(), row["match_id"].as_i64()) else {
+            continue;
+        };
+        holders
+            .entry(number)
+            .or_default()
+            .push((id, !JsonValue::is_null(&row["deleted_at"])));
+    }
+
+    let mut conflicts = Vec::new();
+    let mut released = Vec::new();
+    for (number, holders) in holders.into_iter().filter(|(_, h)| h.len() > 1) {
+        let live = holders.iter().filter(|(_, deleted)| !deleted).map(|(id, _)| *id).collect::<Vec<_>>();
+        if live.len() > 1 {
+            let ids = live.iter().map(ToString::to_string).collect::<Vec<_>>();
+            conflicts.push(format!("{number} (ids {})", ids.join(", ")));
+            continue;
+        }
+        // Keep the live match, or else the newest soft-deleted one
+        let keep = live.first().copied().unwrap_or_else(|| holders[holders.len() - 1].0);
+        released.extend(holders.iter().map(|(id, _)| *id).filter(|id| *id != keep));
+    }
+    if !conflicts.is_empty() {
+        return Err(DbErr::Migration(format!(
+            "live matches share a match number: {}; renumber or delete all but one of each \
+             before adding the unique index",
+            conflicts.join("; ")
+        )));
+    }
+
+    if !released.is_empty() {
+        let update = Query::update()
+            .table(Matches::Table)
+            .value(Matches::MatchId, Option::<i32>::None)
+            .and_where(Expr::col(Matches::Id).is_in(released))
+            .to_owned();
+        db.execute(backend.build(&update)).await?;
+    }
+    Ok(())
+}
+
+#[derive(DeriveIden)]
+enum Matches {
+    Table,
+    Id,
+    MatchId,
+    DeletedAt,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Where a match's stats rows came from, so a download of `stats_url` can
+/// leave a log uploaded by hand alone. Rows imported before this column
+/// existed were uploaded.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MatchPlayerStats::Table)
+                    .add_column(string(MatchPlayerStats::Source).default("upload"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MatchPlayerStats::Table)
+                    .drop_column(MatchPlayerStats::Source)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MatchPlayerStats {
+    Table,
+    Source,
+}
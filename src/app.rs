@@ -12,11 +12,18 @@ use loco_rs::{
     Result,
 };
 use migration::Migrator;
-use sea_orm::DatabaseConnection;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, IdenStatic, Iterable,
+    PrimaryKeyToColumn, Statement,
+};
 
 use crate::{
     controllers, initializers,
-    models::_entities::notes,
+    models::_entities::{
+        achievements, match_participants, match_player_stats, matches, notes, player_achievements,
+        player_aliases, player_elo, player_penalties, players, season_standings, seasons,
+        stats_snapshots, users,
+    },
     tasks,
     workers::downloader::DownloadWorker,
 };
@@ -50,10 +57,19 @@ impl Hooks for App {
 
     fn routes(_ctx: &AppContext) -> AppRoutes {
         AppRoutes::with_default_routes()
+            .add_route(controllers::auth::routes())
             .add_route(controllers::player_elo::routes())
             .add_route(controllers::players::routes())
             .add_route(controllers::matches::routes())
             .add_route(controllers::notes::routes())
+            .add_route(controllers::maps::routes())
+            .add_route(controllers::servers::routes())
+            .add_route(controllers::seasons::routes())
+            .add_route(controllers::teams::routes())
+            .add_route(controllers::calibration::routes())
+            .add_route(controllers::achievements::routes())
+            .add_route(controllers::penalties::routes())
+            .add_route(controllers::live::routes())
     }
 
     fn connect_workers<'a>(p: &'a mut Processor, ctx: &'a AppContext) {
@@ -62,15 +78,94 @@ impl Hooks for App {
 
     fn register_tasks(tasks: &mut Tasks) {
         tasks.register(tasks::seed::SeedData);
+        tasks.register(tasks::sync_match_participants::SyncMatchParticipants);
+        tasks.register(tasks::recompute_elo::RecomputeElo);
+        tasks.register(tasks::archive_season::ArchiveSeason);
+        tasks.register(tasks::calibration_report::CalibrationReport);
+        tasks.register(tasks::evaluate_achievements::EvaluateAchievements);
+        tasks.register(tasks::expire_penalties::ExpirePenalties);
+        tasks.register(tasks::import_stats_logs::ImportStatsLogs);
+        tasks.register(tasks::sync_player_aliases::SyncPlayerAliases);
     }
 
     async fn truncate(db: &DatabaseConnection) -> Result<()> {
+        truncate_table(db, users::Entity).await?;
         truncate_table(db, notes::Entity).await?;
+        truncate_table(db, player_aliases::Entity).await?;
+        truncate_table(db, player_penalties::Entity).await?;
+        truncate_table(db, player_achievements::Entity).await?;
+        truncate_table(db, achievements::Entity).await?;
+        truncate_table(db, season_standings::Entity).await?;
+        truncate_table(db, seasons::Entity).await?;
+        truncate_table(db, stats_snapshots::Entity).await?;
+        truncate_table(db, match_player_stats::Entity).await?;
+        truncate_table(db, match_participants::Entity).await?;
+        truncate_table(db, player_elo::Entity).await?;
+        truncate_table(db, matches::Entity).await?;
+        truncate_table(db, players::Entity).await?;
         Ok(())
     }
 
     async fn seed(db: &DatabaseConnection, base: &Path) -> Result<()> {
+        db::seed::<users::ActiveModel>(db, &base.join("users.yaml").display().to_string()).await?;
         db::seed::<notes::ActiveModel>(db, &base.join("notes.yaml").display().to_string()).await?;
+        db::seed::<players::ActiveModel>(db, &base.join("players.yaml").display().to_string())
+            .await?;
+        db::seed::<matches::ActiveModel>(db, &base.join("matches.yaml").display().to_string())
+            .await?;
+        db::seed::<match_participants::ActiveModel>(
+            db,
+            &base.join("match_participants.yaml").display().to_string(),
+        )
+        .await?;
+        db::seed::<player_elo::ActiveModel>(
+            db,
+            &base.join("player_elo.yaml").display().to_string(),
+        )
+        .await?;
+        db::seed::<seasons::ActiveModel>(db, &base.join("seasons.yaml").display().to_string())
+            .await?;
+        db::seed::<player_penalties::ActiveModel>(
+            db,
+            &base.join("player_penalties.yaml").display().to_string(),
+        )
+        .await?;
+        db::seed::<player_aliases::ActiveModel>(
+            db,
+            &base.join("player_aliases.yaml").display().to_string(),
+        )
+        .await?;
+
+        reset_sequence::<users::Entity>(db).await?;
+        reset_sequence::<notes::Entity>(db).await?;
+        reset_sequence::<players::Entity>(db).await?;
+        reset_sequence::<matches::Entity>(db).await?;
+        reset_sequence::<match_participants::Entity>(db).await?;
+        reset_sequence::<player_elo::Entity>(db).await?;
+        reset_sequence::<seasons::Entity>(db).await?;
+        reset_sequence::<player_penalties::Entity>(db).await?;
+        reset_sequence::<player_aliases::Entity>(db).await?;
         Ok(())
     }
+}
+
+/// Fixtures insert explicit ids, which Postgres sequences don't see. Moves
+/// the sequence of `E`'s primary key past them so later inserts don't collide.
+async fn reset_sequence<E: EntityTrait>(db: &DatabaseConnection) -> Result<()> {
+    if db.get_database_backend() != DbBackend::Postgres {
+        return Ok(());
+    }
+    let Some(key) = E::PrimaryKey::iter().next() else {
+        return Ok(());
+    };
+    let table = E::default().table_name().to_string();
+    let key = key.into_column().as_str().to_string();
+    db.execute(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            r#"SELECT setval(pg_get_serial_sequence('"{table}"', '{key}'), COALESCE(MAX("{key}"), 0) + 1, false) FROM "{table}""#
+        ),
+    ))
+    .await?;
+    Ok(())
 }
\ No newline at end of file
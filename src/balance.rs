@@ -0,0 +1,139 @@
+//! Splits a pickup into the two most evenly matched teams.
+//!
+//! Teams are rated the way [`crate::elo`] rates them, by the average rating
+//! of their players, and a split is better the closer blue's win probability
+//! is to one half. Every split is tried, so the number of players is capped
+//! at [`MAX_PLAYERS`].
+
+use serde::Serialize;
+
+use crate::elo::expected_score;
+
+/// Most players a pickup can be balanced for; 16 players is 6435 splits.
+pub const MAX_PLAYERS: usize = 16;
+pub const DEFAULT_ALTERNATIVES: usize = 3;
+pub const MAX_ALTERNATIVES: usize = 20;
+
+/// Rules a split has to follow. Players are indexes into the ratings.
+#[derive(Clone, Debug, Default)]
+pub struct Constraints {
+    /// Pairs that must play on opposite teams.
+    pub apart: Vec<(usize, usize)>,
+    /// Pairs that must play on the same team.
+    pub together: Vec<(usize, usize)>,
+    /// Blue and red captain.
+    pub captains: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BalanceError {
+    /// Teams can't be even with this many players.
+    PlayerCount(usize),
+    /// A constraint names a player that is not in the pickup.
+    UnknownPlayer(usize),
+    /// No split satisfies the constraints.
+    Unsatisfiable,
+}
+
+impl std::fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PlayerCount(count) => write!(
+                f,
+                "need an even number of players between 2 and {MAX_PLAYERS}, got {count}"
+            ),
+            Self::UnknownPlayer(index) => write!(f, "player {index} is not in the pickup"),
+            Self::Unsatisfiable => write!(f, "no split satisfies the constraints"),
+        }
+    }
+}
+
+impl std::error::Error for BalanceError {}
+
+/// One way to split the pickup, teams as indexes into the ratings.
+#[derive(Clone, Debug, Serialize)]
+pub struct Split {
+    pub blue: Vec<usize>,
+    pub red: Vec<usize>,
+    pub blue_rank: f64,
+    pub red_rank: f64,
+    pub blue_probability: f64,
+}
+
+impl Split {
+    /// How far the split is from an even game, 0 being a coin flip.
+    pub fn imbalance(&self) -> f64 {
+        (self.blue_probability - 0.5).abs()
+    }
+}
+
+/// The `limit` most balanced splits of players rated `ratings` that follow
+/// `constraints`, best first. A split and the same split with the colours
+/// swapped count once; the blue captain, or else the first player, is
+/// always on blue.
+///
+/// # Errors
+///
+/// When the player count is odd or out of range, a constraint names an
+/// unknown player, or no split satisfies the constraints
+pub fn balance(
+    ratings: &[i32],
+    constraints: &Constraints,
+    limit: usize,
+) -> Result<Vec<Split>, BalanceError> {
+    let count = ratings.len();
+    if !(2..=MAX_PLAYERS).contains(&count) || !count.is_multiple_of(2) {
+        return Err(BalanceError::PlayerCount(count));
+    }
+    let pairs = constraints.apart.iter().chain(&constraints.together);
+    if let Some(&index) = pairs
+        .flat_map(|(a, b)| [a, b])
+        .chain(constraints.captains.iter().flat_map(|(a, b)| [a, b]))
+        .find(|&&index| index >= count)
+    {
+        return Err(BalanceError::UnknownPlayer(index));
+    }
+
+    let on_blue = |mask: u32, index: usize| mask & (1 << index) != 0;
+    let anchor = constraints.captains.map_or(0, |(blue, _)| blue);
+    let mut splits = (0..1u32 << count)
+        .filter(|mask| mask.count_ones() as usize == count / 2 && on_blue(*mask, anchor))
+        .filter(|&mask| {
+            !constraints
+                .captains
+                .is_some_and(|(_, red)| on_blue(mask, red))
+                && constraints
+                    .apart
+                    .iter()
+                    .all(|&(a, b)| on_blue(mask, a) != on_blue(mask, b))
+                && constraints
+                    .together
+                    .iter()
+                    .all(|&(a, b)| on_blue(mask, a) == on_blue(mask, b))
+        })
+        .map(|mask| {
+            let (blue, red): (Vec<usize>, Vec<usize>) =
+                (0..count).partition(|&index| on_blue(mask, index));
+            let average = |team: &[usize]| {
+                team.iter().map(|&index| f64::from(ratings[index])).sum::<f64>() / team.len() as f64
+            };
+            let blue_rank = average(&blue);
+            let red_rank = average(&red);
+            Split {
+                blue,
+                red,
+                blue_rank,
+                red_rank,
+                blue_probability: expected_score(blue_rank, red_rank),
+            }
+        })
+        .collect::<Vec<_>>();
+    if splits.is_empty() {
+        return Err(BalanceError::Unsatisfiable);
+    }
+
+    // Stable, so equally balanced splits keep their enumeration order
+    splits.sort_by(|a, b| a.imbalance().total_cmp(&b.imbalance()));
+    splits.truncate(limit.max(1));
+    Ok(splits)
+}
@@ -0,0 +1,14 @@
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use loco_rs::prelude::*;
+
+use crate::models::achievements;
+
+#[debug_handler]
+pub async fn list(State(ctx): State<AppContext>) -> Result<Response> {
+    format::json(achievements::summaries(&ctx.db).await?)
+}
+
+pub fn routes() -> Routes {
+    Routes::new().prefix("api/achievements").add("/", get(list))
+}
@@ -0,0 +1,68 @@
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::controllers::guard::CurrentUser;
+use crate::models::users::{self, Role};
+
+#[derive(Debug, Deserialize)]
+pub struct LoginParams {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub pid: String,
+    pub name: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CurrentResponse {
+    pub pid: String,
+    pub name: String,
+    pub email: String,
+    pub role: Role,
+}
+
+#[debug_handler]
+pub async fn login(State(ctx): State<AppContext>, Json(params): Json<LoginParams>) -> Result<Response> {
+    let user = users::Model::find_by_email(&ctx.db, &params.email)
+        .await
+        .map_err(|_| Error::Unauthorized("unauthorized!".to_string()))?;
+    if !user.verify_password(&params.password) {
+        return Err(Error::Unauthorized("unauthorized!".to_string()));
+    }
+
+    let jwt_secret = ctx.config.get_jwt_config()?;
+    let token = user
+        .generate_jwt(&jwt_secret.secret, &jwt_secret.expiration)
+        .map_err(|_| Error::Unauthorized("unauthorized!".to_string()))?;
+
+    format::json(LoginResponse {
+        token,
+        pid: user.pid.to_string(),
+        name: user.name.clone(),
+        role: user.role(),
+    })
+}
+
+#[debug_handler(state = AppContext)]
+pub async fn current(auth: CurrentUser) -> Result<Response> {
+    format::json(CurrentResponse {
+        pid: auth.user.pid.to_string(),
+        role: auth.user.role(),
+        name: auth.user.name,
+        email: auth.user.email,
+    })
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/auth")
+        .add("/login", post(login))
+        .add("/current", get(current))
+}
@@ -0,0 +1,18 @@
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use axum::extract::Query;
+use loco_rs::prelude::*;
+
+use crate::models::calibration::{self, CalibrationParams};
+
+#[debug_handler]
+pub async fn report(
+    State(ctx): State<AppContext>,
+    Query(params): Query<CalibrationParams>,
+) -> Result<Response> {
+    format::json(calibration::report(&ctx.db, &params).await?)
+}
+
+pub fn routes() -> Routes {
+    Routes::new().prefix("api/calibration").add("/", get(report))
+}
@@ -0,0 +1,128 @@
+//! Extractors that authenticate the caller and check their role.
+//!
+//! Callers send `Authorization: Bearer <token>`, where the token is either a
+//! user's API key (what the bot uses) or a JWT issued by `POST
+//! /api/auth/login`. Handlers take one of [`AdminUser`], [`BotUser`] or
+//! [`ViewerUser`] as an argument to require at least that role.
+
+use std::marker::PhantomData;
+
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+};
+use loco_rs::{
+    app::AppContext,
+    auth::jwt,
+    controller::ErrorDetail,
+    model::{Authenticable, ModelError},
+    Error,
+};
+
+use crate::models::users::{self, Role};
+
+/// The user behind the request's bearer token, whatever their role.
+pub struct CurrentUser {
+    pub user: users::Model,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    AppContext: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ctx: AppContext = AppContext::from_ref(state);
+        let token = bearer_token(parts)?;
+
+        // Anything that isn't an API key may still be a JWT
+        match users::Model::find_by_api_key(&ctx.db, token).await {
+            Ok(user) => return Ok(Self { user }),
+            Err(ModelError::EntityNotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let secret = &ctx.config.get_jwt_config()?.secret;
+        let claims = jwt::JWT::new(secret)
+            .validate(token)
+            .map_err(|_| Error::Unauthorized("invalid token".to_string()))?
+            .claims;
+        let user = users::Model::find_by_claims_key(&ctx.db, &claims.pid)
+            .await
+            .map_err(|err| match err {
+                ModelError::DbErr(_) => err.into(),
+                _ => Error::Unauthorized("unknown user".to_string()),
+            })?;
+        Ok(Self { user })
+    }
+}
+
+fn bearer_token(parts: &Parts) -> Result<&str, Error> {
+    parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| Error::Unauthorized("missing bearer token".to_string()))
+}
+
+/// Minimum role a [`RequireRole`] extractor accepts.
+pub trait MinimumRole: Send + Sync + 'static {
+    const ROLE: Role;
+}
+
+pub struct AdminRole;
+impl MinimumRole for AdminRole {
+    const ROLE: Role = Role::Admin;
+}
+
+pub struct BotRole;
+impl MinimumRole for BotRole {
+    const ROLE: Role = Role::Bot;
+}
+
+pub struct ViewerRole;
+impl MinimumRole for ViewerRole {
+    const ROLE: Role = Role::Viewer;
+}
+
+/// An authenticated user whose role is at least `R::ROLE`. Missing or bad
+/// credentials are rejected with 401, a role that is too low with 403.
+pub struct RequireRole<R: MinimumRole> {
+    pub user: users::Model,
+    role: PhantomData<R>,
+}
+
+#[async_trait]
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    AppContext: FromRef<S>,
+    S: Send + Sync,
+    R: MinimumRole,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let CurrentUser { user } = CurrentUser::from_request_parts(parts, state).await?;
+        if user.role() < R::ROLE {
+            return Err(Error::CustomError(
+                StatusCode::FORBIDDEN,
+                ErrorDetail::new("forbidden".to_string(), format!("requires the {} role", R::ROLE)),
+            ));
+        }
+        Ok(Self {
+            user,
+            role: PhantomData,
+        })
+    }
+}
+
+pub type AdminUser = RequireRole<AdminRole>;
+pub type BotUser = RequireRole<BotRole>;
+pub type ViewerUser = RequireRole<ViewerRole>;
@@ -0,0 +1,68 @@
+#![allow(clippy::unused_async)]
+use std::convert::Infallible;
+
+use axum::debug_handler;
+use axum::extract::Query;
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::{stream, StreamExt};
+use loco_rs::prelude::*;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::live::{self, Envelope};
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LiveParams {
+    /// Same as the `Last-Event-ID` header, for clients that cannot set it.
+    pub last_event_id: Option<u64>,
+}
+
+fn to_event(envelope: &Envelope) -> Event {
+    Event::default()
+        .id(envelope.id.to_string())
+        .event(envelope.event.kind())
+        .json_data(&envelope.event)
+        .unwrap_or_else(|_| Event::default().comment("unserializable event"))
+}
+
+/// Tells the client it missed events and should reload what it shows.
+fn resync() -> Event {
+    Event::default().event("resync").data("{}")
+}
+
+/// Server-sent events for matches created and finished, rating changes and
+/// leaderboard reorders.
+#[debug_handler]
+pub async fn stream_events(
+    headers: HeaderMap,
+    Query(params): Query<LiveParams>,
+) -> Result<Response> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .or(params.last_event_id);
+    let subscription = live::feed().subscribe(last_event_id);
+
+    let replay = match subscription.missed {
+        Some(missed) => missed.iter().map(to_event).collect(),
+        None => vec![resync()],
+    };
+    let updates = stream::unfold(subscription.receiver, |mut receiver| async move {
+        match receiver.recv().await {
+            Ok(envelope) => Some((to_event(&envelope), receiver)),
+            // This client fell behind the channel
+            Err(RecvError::Lagged(_)) => Some((resync(), receiver)),
+            Err(RecvError::Closed) => None,
+        }
+    });
+    let events = stream::iter(replay).chain(updates).map(Ok::<_, Infallible>);
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+pub fn routes() -> Routes {
+    Routes::new().prefix("api/live").add("/", get(stream_events))
+}
@@ -0,0 +1,33 @@
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use axum::extract::Query;
+use loco_rs::prelude::*;
+
+use crate::models::map_stats::{self, MapParams};
+
+#[debug_handler]
+pub async fn list(
+    State(ctx): State<AppContext>,
+    Query(params): Query<MapParams>,
+) -> Result<Response> {
+    format::json(map_stats::summaries(&ctx.db, &params).await?)
+}
+
+#[debug_handler]
+pub async fn get_one(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+    Query(params): Query<MapParams>,
+) -> Result<Response> {
+    let detail = map_stats::detail(&ctx.db, &name, &params)
+        .await?
+        .ok_or(Error::NotFound)?;
+    format::json(detail)
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/maps")
+        .add("/", get(list))
+        .add("/:name", get(get_one))
+}
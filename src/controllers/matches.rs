@@ -1,19 +1,39 @@
 #![allow(clippy::unused_async)]
+use std::collections::{BTreeMap, HashMap};
+
 use axum::debug_handler;
+use axum::routing::patch;
+use axum::extract::Query;
 use loco_rs::prelude::*;
-use sea_orm::{DbBackend, EntityTrait, QueryFilter, ColumnTrait, Condition, Statement};
-use sea_orm::prelude::Expr;
-use serde::Serialize;
-use crate::models::_entities::matches::{Entity as Matches, Column as MatchesColumn};
+use sea_orm::{EntityTrait, QueryFilter, QueryOrder, ColumnTrait, SqlErr};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::models::_entities::match_participants::{Entity as MatchParticipants, Column as ParticipantsColumn};
+use crate::controllers::guard::BotUser;
+use crate::elo::{self, EloConfig};
+use crate::live::{self, MatchReport};
+use crate::models::_entities::matches::{Entity as Matches, Column as MatchesColumn, ActiveModel as MatchActiveModel, Model as MatchModel};
 use crate::models::_entities::players::{Entity as Players, Column as PlayersColumn, Model as PlayerModel};
+use crate::models::_entities::player_elo::{Entity as PlayerElo, Column as PlayerEloColumn, Model as EloModel};
+use crate::models::match_participants;
+use crate::models::match_player_stats;
+use crate::models::matches::{DateRange, ListParams, MatchResult, Page, Record, ScoreLine};
+use crate::models::player_elo;
+use crate::models::stats_snapshots;
+use crate::stats_log::ParsedLog;
+use crate::workers::downloader::{DownloadWorker, DownloadWorkerArgs};
+use loco_rs::worker::AppWorker;
 
 #[debug_handler]
-pub async fn list(State(ctx): State<AppContext>) -> Result<Response> {
-    format::json(Matches::find().filter(MatchesColumn::GameType.eq("4v4")).filter(MatchesColumn::DeletedAt.is_null()).all(&ctx.db).await?)
+pub async fn list(
+    State(ctx): State<AppContext>,
+    Query(params): Query<ListParams>,
+) -> Result<Response> {
+    format::json(params.fetch(&ctx.db).await?)
 }
 
 #[debug_handler]
-pub async fn get_one(Path(id): Path<u32>, State(ctx): State<AppContext>) -> Result<Response> {
+pub async fn get_one(Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
     let match_item = Matches::find_by_id(id).one(&ctx.db).await?;
     match match_item {
         Some(m) => format::json(m),
@@ -21,31 +41,144 @@ pub async fn get_one(Path(id): Path<u32>, State(ctx): State<AppContext>) -> Resu
     }
 }
 
+/// A roster player of a match detail, with the rating swing of that match.
+#[derive(Serialize)]
+struct DetailPlayer {
+    #[serde(flatten)]
+    player: PlayerModel,
+    elo_before: Option<i32>,
+    elo_after: Option<i32>,
+    elo_change: Option<i32>,
+}
+
+/// What the ratings predicted against what happened. Teams are `"blue"` or
+/// `"red"`; `actual` is also `"draw"`, and `null` while undecided.
+#[derive(Serialize)]
+struct Prediction {
+    blue_probability: Option<f32>,
+    red_probability: Option<f32>,
+    predicted: Option<&'static str>,
+    actual: Option<&'static str>,
+    /// Whether the favourite won; `null` for draws, undecided matches and
+    /// even odds.
+    correct: Option<bool>,
+}
+
+impl Prediction {
+    fn for_match(item: &MatchModel) -> Self {
+        let blue_probability = item.blue_probability;
+        let red_probability = item
+            .red_probability
+            .or_else(|| blue_probability.map(|p| 1.0 - p));
+        let predicted = match (blue_probability, red_probability) {
+            (Some(blue), Some(red)) if blue > red => Some(match_participants::BLUE_TEAM),
+            (Some(blue), Some(red)) if red > blue => Some(match_participants::RED_TEAM),
+            _ => None,
+        };
+        let actual = match item.match_outcome {
+            Some(0) => Some("draw"),
+            Some(1) => Some(match_participants::BLUE_TEAM),
+            Some(2) => Some(match_participants::RED_TEAM),
+            _ => None,
+        };
+        let correct = match (predicted, actual) {
+            (Some(predicted), Some(actual)) if actual != "draw" => Some(predicted == actual),
+            _ => None,
+        };
+        Self {
+            blue_probability,
+            red_probability,
+            predicted,
+            actual,
+            correct,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MatchDetail {
+    match_data: MatchModel,
+    blue_team: Vec<DetailPlayer>,
+    red_team: Vec<DetailPlayer>,
+    prediction: Prediction,
+    score: Option<ScoreLine>,
+}
+
+async fn detail_players<C: ConnectionTrait>(
+    db: &C,
+    players: Vec<PlayerModel>,
+    entries: &HashMap<i64, EloModel>,
+) -> Result<Vec<DetailPlayer>> {
+    let mut detailed = Vec::with_capacity(players.len());
+    for player in players {
+        let entry = player
+            .discord_id
+            .as_deref()
+            .and_then(|id| id.trim().parse::<i64>().ok())
+            .and_then(|id| entries.get(&id));
+        let elo_after = entry.and_then(|e| e.player_elos);
+        let elo_before = match entry {
+            Some(entry) => entry.previous(db).await?.and_then(|e| e.player_elos),
+            None => None,
+        };
+        detailed.push(DetailPlayer {
+            player,
+            elo_before,
+            elo_after,
+            elo_change: elo_before.zip(elo_after).map(|(before, after)| after - before),
+        });
+    }
+    Ok(detailed)
+}
+
 #[debug_handler]
-pub async fn get_matches_by_player_name(Path(player_name): Path<String>, State(ctx): State<AppContext>) -> Result<Response> {
-    // First, find the player's discord_id
-    let statement = Statement::from_sql_and_values(
-        DbBackend::MySql,
-        r#"SELECT * FROM players WHERE LOWER(player_name) = LOWER(?)"#,
-        [player_name.clone().into()]
-    );
-    let player = Players::find()
-        .from_raw_sql(statement)
+pub async fn get_detail(Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let match_data = Matches::find_by_id(id)
         .one(&ctx.db)
-        .await?;
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let rosters = match_participants::rosters_for(&ctx.db, vec![match_data.id])
+        .await?
+        .remove(&match_data.id)
+        .unwrap_or_default();
+
+    // The entries this match wrote hold each player's rating after it
+    let entries: HashMap<i64, EloModel> = match match_data.match_id {
+        Some(match_id) => PlayerElo::find()
+            .filter(PlayerEloColumn::MatchId.eq(i64::from(match_id)))
+            .all(&ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(|e| Some((e.discord_id?, e)))
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    format::json(MatchDetail {
+        blue_team: detail_players(&ctx.db, rosters.blue, &entries).await?,
+        red_team: detail_players(&ctx.db, rosters.red, &entries).await?,
+        prediction: Prediction::for_match(&match_data),
+        score: match_data.score_line(),
+        match_data,
+    })
+}
+
+#[debug_handler]
+pub async fn get_matches_by_player_name(Path(player_name): Path<String>, State(ctx): State<AppContext>) -> Result<Response> {
+    // First, find the player
+    let player = PlayerModel::find_by_name(&ctx.db, &player_name).await?;
 
-    let discord_id = match player {
-        Some(p) => p.discord_id.unwrap_or_default(),
+    let player_id = match player {
+        Some(p) => p.id,
         None => return Err(Error::NotFound),
     };
 
-    // Now, find all matches where this discord_id is in either blue_team or red_team
+    // Now, find all matches this player took part in, on either team
     let matches = Matches::find()
-    .filter(
-        Condition::any()
-            .add(Expr::col(MatchesColumn::BlueTeam).like(format!("%{}%", discord_id)))
-            .add(Expr::col(MatchesColumn::RedTeam).like(format!("%{}%", discord_id)))
-    ).filter(MatchesColumn::DeletedAt.is_null())
+        .inner_join(MatchParticipants)
+        .filter(ParticipantsColumn::PlayerId.eq(player_id))
+        .filter(MatchesColumn::DeletedAt.is_null())
         .all(&ctx.db)
         .await?;
 
@@ -58,180 +191,622 @@ pub async fn echo(req_body: String) -> String {
 }
 
 #[derive(Serialize)]
-struct WinrateResponse {
-    games_played: usize,
-    wins: usize,
-    losses: usize,
-    winrate: f64,
+struct DuoStatsResponse {
+    #[serde(flatten)]
+    overall: Record,
+    by_map: BTreeMap<String, Record>,
+    by_game_type: BTreeMap<String, Record>,
 }
 
-
+/// Record of two players when they were on the same team, optionally limited
+/// to a `from`/`to` date range.
 #[debug_handler]
-pub async fn get_same_team_winrate(
+pub async fn get_duo_stats(
     Path((player1_name, player2_name)): Path<(String, String)>,
+    Query(range): Query<DateRange>,
     State(ctx): State<AppContext>
 ) -> Result<Response> {
-
-    let player = Players::find()
-        .filter(PlayersColumn::PlayerName.eq(&player1_name))
-        .one(&ctx.db)
-        .await?;
-
-    let player1_discord_id = match player {
-        Some(p) => p.discord_id.unwrap_or_default(),
-        None => return Err(Error::NotFound),
-    };
-
-    let player_2 = Players::find()
-        .filter(PlayersColumn::PlayerName.eq(&player2_name))
-        .one(&ctx.db)
-        .await?;
-
-    let player2_discord_id = match player_2 {
-        Some(p) => p.discord_id.unwrap_or_default(),
-        None => return Err(Error::NotFound),
-    };
+    let player1 = PlayerModel::find_by_name(&ctx.db, &player1_name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let player2 = PlayerModel::find_by_name(&ctx.db, &player2_name)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let shared = match_participants::shared_matches(&ctx.db, player1.id, player2.id)
+        .await?
+        .into_iter()
+        .filter(match_participants::SharedMatch::same_team)
+        .map(|m| (m.match_id, m.first_team))
+        .collect::<HashMap<_, _>>();
 
     let matches = Matches::find()
-    .filter(
-        Condition::any()
-            .add(
-                Condition::all()
-                .add(Expr::col(MatchesColumn::BlueTeam).like(format!("%{}%", player1_discord_id)))
-                .add(Expr::col(MatchesColumn::BlueTeam).like(format!("%{}%", player2_discord_id)))
-            )
-            .add(
-                Condition::all()
-                .add(Expr::col(MatchesColumn::RedTeam).like(format!("%{}%", player1_discord_id)))
-                .add(Expr::col(MatchesColumn::RedTeam).like(format!("%{}%", player2_discord_id)))
-            )
-    ).filter(MatchesColumn::DeletedAt.is_null())
+        .filter(MatchesColumn::Id.is_in(shared.keys().copied()))
+        .filter(MatchesColumn::DeletedAt.is_null())
+        .filter(range.condition())
         .all(&ctx.db)
         .await?;
 
-    let (wins, losses) = matches.iter().fold((0, 0), |(w, l), m| {
-        let is_blue_team = m.blue_team.as_ref().unwrap().contains(&player1_discord_id) && m.blue_team.as_ref().unwrap().contains(&player2_discord_id);
-        let is_winner = (is_blue_team && m.match_outcome.unwrap_or_default() == 1) || (!is_blue_team && m.match_outcome.unwrap_or_default() == 2);
-        if is_winner { (w + 1, l) } else { (w, l + 1) }
-    });
+    let mut stats = DuoStatsResponse {
+        overall: Record::default(),
+        by_map: BTreeMap::new(),
+        by_game_type: BTreeMap::new(),
+    };
+    for m in &matches {
+        let team = shared.get(&m.id).map(String::as_str).unwrap_or_default();
+        let result = m.result_for(team);
+        stats.overall.add(result);
+        stats
+            .by_map
+            .entry(m.map.clone().unwrap_or_else(|| "unknown".to_string()))
+            .or_default()
+            .add(result);
+        stats
+            .by_game_type
+            .entry(m.game_type.clone().unwrap_or_else(|| "unknown".to_string()))
+            .or_default()
+            .add(result);
+    }
+
+    format::json(stats)
+}
 
-    let games_played = wins + losses;
-    let winrate = if games_played > 0 { (wins as f64) / (games_played as f64) } else { 0.0 };
+#[derive(Serialize)]
+struct VersusPlayer {
+    name: Option<String>,
+    wins: usize,
+    winrate: f64,
+    elo_change: i32,
+}
 
-    format::json(WinrateResponse {
-        games_played,
-        wins,
-        losses,
-        winrate,
-    })
+#[derive(Serialize)]
+struct VersusResponse {
+    games_played: usize,
+    draws: usize,
+    undecided: usize,
+    average_score_margin: Option<f64>,
+    player1: VersusPlayer,
+    player2: VersusPlayer,
 }
 
-/*#[debug_handler]
+#[debug_handler]
 pub async fn get_versus_winrate(
     Path((player1_name, player2_name)): Path<(String, String)>,
     State(ctx): State<AppContext>
 ) -> Result<Response> {
-    let (player1, player2) = get_players(&ctx, &player1_name, &player2_name).await?;
+    let player1 = PlayerModel::find_by_name(&ctx.db, &player1_name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let player2 = PlayerModel::find_by_name(&ctx.db, &player2_name)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // Only games where the two were on opposite teams, keyed by player 1's team
+    let opposed = match_participants::shared_matches(&ctx.db, player1.id, player2.id)
+        .await?
+        .into_iter()
+        .filter(|m| !m.same_team())
+        .map(|m| (m.match_id, m.first_team))
+        .collect::<HashMap<_, _>>();
 
-    let matches = get_matches_with_players(&ctx, &player1.discord_id, &player2.discord_id).await?;
+    let matches = Matches::find()
+        .filter(MatchesColumn::Id.is_in(opposed.keys().copied()))
+        .filter(MatchesColumn::DeletedAt.is_null())
+        .all(&ctx.db)
+        .await?;
 
-    let (player1_wins, player2_wins) = count_versus_wins(&matches, &player1.discord_id, &player2.discord_id);
+    let starting_rating = EloConfig::from_context(&ctx)?.starting_rating;
+    let player1_changes = player_elo::changes_by_match(
+        &EloModel::history_for_player(&ctx.db, &player1).await?,
+        starting_rating,
+    );
+    let player2_changes = player_elo::changes_by_match(
+        &EloModel::history_for_player(&ctx.db, &player2).await?,
+        starting_rating,
+    );
 
-    let games_played = player1_wins + player2_wins;
-    let player1_winrate = if games_played > 0 { (player1_wins as f64) / (games_played as f64) } else { 0.0 };
-    let player2_winrate = if games_played > 0 { (player2_wins as f64) / (games_played as f64) } else { 0.0 };
+    let (mut player1_wins, mut player2_wins, mut draws, mut undecided) = (0, 0, 0, 0);
+    let mut margins = Vec::new();
+    let (mut player1_elo_change, mut player2_elo_change) = (0, 0);
+    for m in &matches {
+        let team = opposed.get(&m.id).map(String::as_str).unwrap_or_default();
+        // Score margin from player 1's side of the game
+        let scores = m.winning_score.zip(m.losing_score);
+        let margin = match m.result_for(team) {
+            MatchResult::Win => {
+                player1_wins += 1;
+                scores.map(|(w, l)| w - l)
+            }
+            MatchResult::Loss => {
+                player2_wins += 1;
+                scores.map(|(w, l)| l - w)
+            }
+            MatchResult::Draw => {
+                draws += 1;
+                Some(0)
+            }
+            MatchResult::Undecided => {
+                undecided += 1;
+                continue;
+            }
+        };
+        margins.extend(margin);
+
+        if let Some(match_id) = m.match_id.map(i64::from) {
+            player1_elo_change += player1_changes.get(&match_id).copied().unwrap_or_default();
+            player2_elo_change += player2_changes.get(&match_id).copied().unwrap_or_default();
+        }
+    }
+
+    let games_played = player1_wins + player2_wins + draws;
+    let winrate = |wins: usize| if games_played > 0 { (wins as f64) / (games_played as f64) } else { 0.0 };
+    let average_score_margin = if margins.is_empty() {
+        None
+    } else {
+        Some(f64::from(margins.iter().sum::<i32>()) / (margins.len() as f64))
+    };
 
-    format::json(json!({
-        "games_played": games_played,
-        "player1": {
-            "name": player1_name,
-            "wins": player1_wins,
-            "winrate": player1_winrate,
+    format::json(VersusResponse {
+        games_played,
+        draws,
+        undecided,
+        average_score_margin,
+        player1: VersusPlayer {
+            name: player1.player_name,
+            wins: player1_wins,
+            winrate: winrate(player1_wins),
+            elo_change: player1_elo_change,
         },
-        "player2": {
-            "name": player2_name,
-            "wins": player2_wins,
-            "winrate": player2_winrate,
+        player2: VersusPlayer {
+            name: player2.player_name,
+            wins: player2_wins,
+            winrate: winrate(player2_wins),
+            elo_change: player2_elo_change,
         },
-    }))
-}*/
+    })
+}
 
 #[derive(Serialize)]
 struct MatchWithPlayers {
     match_data: crate::models::_entities::matches::Model,
-    blue_team_players: Vec<PlayerModel>,
-    red_team_players: Vec<PlayerModel>,
+    blue_team_players: Vec<RosterPlayer>,
+    red_team_players: Vec<RosterPlayer>,
 }
 
-#[debug_handler]
-pub async fn list_with_players(State(ctx): State<AppContext>) -> Result<Response> {
-    // Get all matches
-    let matches = Matches::find()
-        .filter(MatchesColumn::GameType.eq("4v4"))
-        .filter(MatchesColumn::DeletedAt.is_null())
-        .all(&ctx.db)
-        .await?;
+/// A player of a match roster, with the rating they had in that match.
+#[derive(Serialize)]
+struct RosterPlayer {
+    #[serde(flatten)]
+    player: PlayerModel,
+    /// Rating recorded in `player_elo` for this match, if any.
+    match_elo: Option<i32>,
+}
 
-    // Get all players
-    let players = Players::find()
-        .all(&ctx.db)
-        .await?;
+impl RosterPlayer {
+    fn resolve(
+        players: Vec<PlayerModel>,
+        match_id: Option<i32>,
+        ratings: &HashMap<(i64, i64), i32>,
+    ) -> Vec<Self> {
+        players
+            .into_iter()
+            .map(|player| {
+                let discord_id = player
+                    .discord_id
+                    .as_deref()
+                    .and_then(|id| id.trim().parse::<i64>().ok());
+                let match_elo = match (match_id, discord_id) {
+                    (Some(match_id), Some(discord_id)) => {
+                        ratings.get(&(i64::from(match_id), discord_id)).copied()
+                    }
+                    _ => None,
+                };
+                Self { player, match_elo }
+            })
+            .collect()
+    }
+}
 
-    // Create a map of discord_id to player for quick lookups
-    let player_map: std::collections::HashMap<String, PlayerModel> = players
-        .into_iter()
-        .filter_map(|p| {
-            p.discord_id.clone().map(|id| (id, p))
-        })
-        .collect();
+#[debug_handler]
+pub async fn list_with_players(
+    State(ctx): State<AppContext>,
+    Query(params): Query<ListParams>,
+) -> Result<Response> {
+    let page = params.fetch(&ctx.db).await?;
+
+    // Resolve the rosters of this page's matches through their participants,
+    // which only loads the players that appear on the page
+    let mut rosters = match_participants::rosters_for(
+        &ctx.db,
+        page.items.iter().map(|m| m.id).collect(),
+    )
+    .await?;
+    let ratings = player_elo::ratings_for_matches(
+        &ctx.db,
+        page.items
+            .iter()
+            .filter_map(|m| m.match_id.map(i64::from))
+            .collect(),
+    )
+    .await?;
 
     // Combine match data with player data
-    let matches_with_players: Vec<MatchWithPlayers> = matches
-        .into_iter()
+    let matches_with_players: Page<MatchWithPlayers> = page
         .map(|match_data| {
-            let blue_team_ids = match_data.blue_team
-                .clone()
-                .unwrap_or_default()
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect::<Vec<String>>();
-
-            let red_team_ids = match_data.red_team
-                .clone()
-                .unwrap_or_default()
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect::<Vec<String>>();
-
-            let blue_team_players = blue_team_ids
-                .iter()
-                .filter_map(|id| player_map.get(id).cloned())
-                .collect();
-
-            let red_team_players = red_team_ids
-                .iter()
-                .filter_map(|id| player_map.get(id).cloned())
-                .collect();
-
+            let rosters = rosters.remove(&match_data.id).unwrap_or_default();
             MatchWithPlayers {
+                blue_team_players: RosterPlayer::resolve(rosters.blue, match_data.match_id, &ratings),
+                red_team_players: RosterPlayer::resolve(rosters.red, match_data.match_id, &ratings),
                 match_data,
-                blue_team_players,
-                red_team_players,
             }
-        })
-        .collect();
+        });
 
     format::json(matches_with_players)
 }
 
+/// A match report as sent by the bot. Teams are lists of Discord IDs.
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+pub struct MatchParams {
+    pub match_id: Option<i32>,
+    #[validate(length(min = 1, max = 16))]
+    pub blue_team: Vec<String>,
+    #[validate(length(min = 1, max = 16))]
+    pub red_team: Vec<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub map: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub server: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub game_type: Option<String>,
+    #[validate(range(min = 0, max = 2))]
+    pub match_outcome: Option<i32>,
+    #[validate(range(min = 0))]
+    pub winning_score: Option<i32>,
+    #[validate(range(min = 0))]
+    pub losing_score: Option<i32>,
+    #[validate(url)]
+    pub stats_url: Option<String>,
+}
+
+/// Partial update of a reported match; absent fields are left unchanged.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MatchPatch {
+    pub match_id: Option<i32>,
+    pub blue_team: Option<Vec<String>>,
+    pub red_team: Option<Vec<String>>,
+    pub map: Option<String>,
+    pub server: Option<String>,
+    pub game_type: Option<String>,
+    pub match_outcome: Option<i32>,
+    pub winning_score: Option<i32>,
+    pub losing_score: Option<i32>,
+    pub stats_url: Option<String>,
+}
+
+impl MatchParams {
+    fn from_model(item: &MatchModel) -> Self {
+        let roster = |team: &Option<String>| {
+            match_participants::roster_ids(team.as_deref())
+                .into_iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        Self {
+            match_id: item.match_id,
+            blue_team: roster(&item.blue_team),
+            red_team: roster(&item.red_team),
+            map: item.map.clone(),
+            server: item.server.clone(),
+            game_type: item.game_type.clone(),
+            match_outcome: item.match_outcome,
+            winning_score: item.winning_score,
+            losing_score: item.losing_score,
+            stats_url: item.stats_url.clone(),
+        }
+    }
+
+    fn merge(&mut self, patch: MatchPatch) {
+        self.match_id = patch.match_id.or(self.match_id);
+        self.blue_team = patch.blue_team.unwrap_or_else(|| self.blue_team.clone());
+        self.red_team = patch.red_team.unwrap_or_else(|| self.red_team.clone());
+        self.map = patch.map.or_else(|| self.map.clone());
+        self.server = patch.server.or_else(|| self.server.clone());
+        self.game_type = patch.game_type.or_else(|| self.game_type.clone());
+        self.match_outcome = patch.match_outcome.or(self.match_outcome);
+        self.winning_score = patch.winning_score.or(self.winning_score);
+        self.losing_score = patch.losing_score.or(self.losing_score);
+        self.stats_url = patch.stats_url.or_else(|| self.stats_url.clone());
+    }
+
+    fn check(&self) -> Result<()> {
+        self.validate()
+            .map_err(|e| Error::BadRequest(e.to_string()))?;
+
+        let mut seen = std::collections::HashSet::new();
+        if let Some(id) = self
+            .blue_team
+            .iter()
+            .chain(&self.red_team)
+            .find(|id| !seen.insert(id.trim()))
+        {
+            return Err(Error::BadRequest(format!("player {id} is listed twice")));
+        }
+
+        if let (Some(outcome), Some(winning), Some(losing)) =
+            (self.match_outcome, self.winning_score, self.losing_score)
+        {
+            if outcome == 0 && winning != losing {
+                return Err(Error::BadRequest("a draw must have equal scores".to_string()));
+            }
+            if outcome != 0 && winning < losing {
+                return Err(Error::BadRequest(
+                    "winning_score is lower than losing_score".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&self, item: &mut MatchActiveModel) {
+        item.match_id = Set(self.match_id);
+        item.blue_team = Set(Some(self.blue_team.join(",")));
+        item.red_team = Set(Some(self.red_team.join(",")));
+        item.map = Set(self.map.clone());
+        item.server = Set(self.server.clone());
+        item.game_type = Set(self.game_type.clone());
+        item.match_outcome = Set(self.match_outcome);
+        item.winning_score = Set(self.winning_score);
+        item.losing_score = Set(self.losing_score);
+        item.stats_url = Set(self.stats_url.clone());
+    }
+
+    /// Rejects Discord IDs that have no player row.
+    async fn check_players<C: ConnectionTrait>(&self, db: &C) -> Result<()> {
+        let ids = self
+            .blue_team
+            .iter()
+            .chain(&self.red_team)
+            .map(|id| id.trim().to_string())
+            .collect::<Vec<_>>();
+        let known = Players::find()
+            .filter(PlayersColumn::DiscordId.is_in(ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|p| p.discord_id)
+            .collect::<std::collections::HashSet<_>>();
+        let unknown = ids.into_iter().filter(|id| !known.contains(id)).collect::<Vec<_>>();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::BadRequest(format!("unknown players: {}", unknown.join(", "))))
+        }
+    }
+}
+
+/// Queues a download of the match's stats page. A failure to queue does not
+/// fail the report; the page can be fetched again by updating `stats_url`.
+async fn enqueue_stats_download(ctx: &AppContext, match_data: &MatchModel) {
+    let args = DownloadWorkerArgs {
+        match_id: match_data.id,
+    };
+    if let Err(err) = DownloadWorker::perform_later(ctx, args).await {
+        tracing::warn!(match_id = match_data.id, error = %err, "could not queue stats download");
+    }
+}
+
+#[derive(Serialize)]
+struct ReportedMatch {
+    match_data: MatchModel,
+    elo_entries: Vec<EloModel>,
+}
+
+/// Reports without a match number retry this many times when a concurrent
+/// report takes the number they were given.
+const MATCH_ID_ATTEMPTS: usize = 3;
+
+/// Next bot match number, for reports that don't carry one.
+async fn next_match_id<C: ConnectionTrait>(db: &C) -> Result<i32> {
+    let last = Matches::find()
+        .filter(MatchesColumn::MatchId.is_not_null())
+        .order_by_desc(MatchesColumn::MatchId)
+        .one(db)
+        .await?
+        .and_then(|m| m.match_id);
+    Ok(last.unwrap_or_default() + 1)
+}
+
+/// Whether `err` is the unique index on `matches.match_id` rejecting a
+/// number that is already taken.
+fn is_duplicate_match_id(err: &DbErr) -> bool {
+    matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_)))
+}
+
+fn duplicate_match_id(match_id: Option<i32>) -> Error {
+    Error::BadRequest(format!(
+        "match {} was already reported",
+        match_id.unwrap_or_default()
+    ))
+}
+
+#[debug_handler]
+pub async fn add(
+    _auth: BotUser,
+    State(ctx): State<AppContext>,
+    Json(params): Json<MatchParams>,
+) -> Result<Response> {
+    params.check()?;
+    let config = EloConfig::from_context(&ctx)?;
+    let standings_before = live::standings(&ctx.db).await?;
+
+    let mut attempt = 1;
+    let (match_data, elo_entries) = loop {
+        let txn = ctx.db.begin().await?;
+        params.check_players(&txn).await?;
+
+        let mut item = MatchActiveModel {
+            ..Default::default()
+        };
+        params.update(&mut item);
+        if params.match_id.is_none() {
+            item.match_id = Set(Some(next_match_id(&txn).await?));
+        }
+        let item = match item.insert(&txn).await {
+            Ok(item) => item,
+            Err(err) if is_duplicate_match_id(&err) => {
+                txn.rollback().await?;
+                if params.match_id.is_none() && attempt < MATCH_ID_ATTEMPTS {
+                    attempt += 1;
+                    continue;
+                }
+                return Err(duplicate_match_id(params.match_id));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        match_participants::ActiveModel::sync_for_match(&txn, &item).await?;
+        let recorded = elo::record_result(&txn, &config, item).await?;
+        txn.commit().await?;
+        break recorded;
+    };
+
+    if match_data.stats_url.is_some() {
+        enqueue_stats_download(&ctx, &match_data).await;
+    }
+    MatchReport {
+        match_data: match_data.clone(),
+        elo_entries: elo_entries.clone(),
+        created: true,
+        previous_outcome: None,
+        standings_before,
+    }
+    .publish(&ctx.db)
+    .await;
+    format::json(ReportedMatch {
+        match_data,
+        elo_entries,
+    })
+}
+
+#[debug_handler]
+pub async fn update(
+    _auth: BotUser,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(patch): Json<MatchPatch>,
+) -> Result<Response> {
+    let config = EloConfig::from_context(&ctx)?;
+    let standings_before = live::standings(&ctx.db).await?;
+
+    let txn = ctx.db.begin().await?;
+    let existing = Matches::find_by_id(id)
+        .one(&txn)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut params = MatchParams::from_model(&existing);
+    params.merge(patch);
+    params.check()?;
+    params.check_players(&txn).await?;
+
+    // A new result or roster means the old rating changes no longer apply
+    let previous = MatchParams::from_model(&existing);
+    let rescore = params.match_outcome != previous.match_outcome
+        || params.blue_team != previous.blue_team
+        || params.red_team != previous.red_team
+        || params.match_id != previous.match_id;
+    if rescore {
+        elo::revert_result(&txn, &config, &existing).await?;
+    }
+
+    let mut item = existing.into_active_model();
+    params.update(&mut item);
+    let item = item.update(&txn).await.map_err(|err| {
+        if is_duplicate_match_id(&err) {
+            duplicate_match_id(params.match_id)
+        } else {
+            err.into()
+        }
+    })?;
+
+    let (match_data, elo_entries) = if rescore {
+        match_participants::ActiveModel::sync_for_match(&txn, &item).await?;
+        elo::record_result(&txn, &config, item).await?
+    } else {
+        (item, vec![])
+    };
+    txn.commit().await?;
+
+    if match_data.stats_url.is_some() && match_data.stats_url != previous.stats_url {
+        enqueue_stats_download(&ctx, &match_data).await;
+    }
+    MatchReport {
+        match_data: match_data.clone(),
+        elo_entries: elo_entries.clone(),
+        created: false,
+        previous_outcome: previous.match_outcome,
+        standings_before,
+    }
+    .publish(&ctx.db)
+    .await;
+    format::json(ReportedMatch {
+        match_data,
+        elo_entries,
+    })
+}
+
+/// Imports a server log sent as the raw request body into the match's
+/// per-player stats, replacing any earlier import.
+#[debug_handler]
+pub async fn upload_stats_log(
+    _auth: BotUser,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+    body: String,
+) -> Result<Response> {
+    let match_data = Matches::find_by_id(id)
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let log = ParsedLog::parse(&body);
+    if log.players.is_empty() {
+        return Err(Error::BadRequest("the log has no player events".to_string()));
+    }
+    let txn = ctx.db.begin().await?;
+    let summary = match_player_stats::import(&txn, &match_data, &log, match_player_stats::UPLOADED).await?;
+    txn.commit().await?;
+    format::json(summary)
+}
+
+#[debug_handler]
+pub async fn get_player_stats(Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    format::json(match_player_stats::for_match(&ctx.db, id).await?)
+}
+
+/// The last download of the match's `stats_url`.
+#[debug_handler]
+pub async fn get_stats_snapshot(Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    format::json(
+        stats_snapshots::for_match(&ctx.db, id)
+            .await?
+            .ok_or(Error::NotFound)?,
+    )
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("api/matches")
         .add("/", get(list))
+        .add("/", post(add))
         .add("/with-players", get(list_with_players))
         .add("/:id", get(get_one))
+        .add("/:id", patch(update))
+        .add("/:id/detail", get(get_detail))
+        .add("/:id/stats-log", post(upload_stats_log))
+        .add("/:id/player-stats", get(get_player_stats))
+        .add("/:id/stats-snapshot", get(get_stats_snapshot))
         .add("/echo", post(echo))
         .add("/player/:player_name", get(get_matches_by_player_name))
-        .add("/same-team-winrate/:player1_name/:player2_name", get(get_same_team_winrate))
-        //.add("/versus-winrate/:player1_name/:player2_name", get(get_versus_winrate))
+        .add("/duo-stats/:player1_name/:player2_name", get(get_duo_stats))
+        .add("/same-team-winrate/:player1_name/:player2_name", get(get_duo_stats))
+        .add("/versus-winrate/:player1_name/:player2_name", get(get_versus_winrate))
 }
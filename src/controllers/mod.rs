@@ -1,4 +1,14 @@
+pub mod achievements;
+pub mod auth;
+pub mod calibration;
+pub mod guard;
+pub mod live;
+pub mod maps;
 pub mod notes;
+pub mod penalties;
 pub mod matches;
 pub mod players;
-pub mod player_elo;
\ No newline at end of file
+pub mod player_elo;
+pub mod seasons;
+pub mod servers;
+pub mod teams;
\ No newline at end of file
@@ -0,0 +1,125 @@
+#![allow(clippy::unused_async)]
+use std::collections::HashMap;
+
+use axum::debug_handler;
+use chrono::Utc;
+use loco_rs::prelude::*;
+use sea_orm::prelude::DateTimeUtc;
+use serde::{Deserialize, Serialize};
+
+use crate::controllers::guard::AdminUser;
+use crate::models::_entities::{matches, players};
+use crate::models::player_penalties::{ActiveModel, Entity, Model};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Params {
+    /// Player name or Discord ID.
+    pub player: String,
+    pub reason: String,
+    /// Open-ended when absent.
+    pub expires_at: Option<DateTimeUtc>,
+    /// `matches.id` of the match the penalty is for.
+    pub match_id: Option<i32>,
+}
+
+#[derive(Serialize)]
+struct PenaltyView {
+    #[serde(flatten)]
+    penalty: Model,
+    player_name: Option<String>,
+    active: bool,
+}
+
+async fn load_item(ctx: &AppContext, id: i32) -> Result<Model> {
+    Entity::find_by_id(id)
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+async fn views(ctx: &AppContext, penalties: Vec<Model>) -> Result<Vec<PenaltyView>> {
+    let names = players::Entity::find()
+        .filter(players::Column::Id.is_in(penalties.iter().map(|p| p.player_id)))
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.player_name))
+        .collect::<HashMap<_, _>>();
+    let now = Utc::now();
+    Ok(penalties
+        .into_iter()
+        .map(|penalty| PenaltyView {
+            player_name: names.get(&penalty.player_id).cloned().flatten(),
+            active: penalty.is_active(now),
+            penalty,
+        })
+        .collect())
+}
+
+/// Penalties in force now: the current dunces.
+#[debug_handler]
+pub async fn list_active(State(ctx): State<AppContext>) -> Result<Response> {
+    let active = Model::active(&ctx.db).await?;
+    format::json(views(&ctx, active).await?)
+}
+
+#[debug_handler]
+pub async fn add(
+    auth: AdminUser,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    let player = players::Model::find_by_name_or_discord_id(&ctx.db, params.player.trim())
+        .await?
+        .ok_or_else(|| Error::BadRequest(format!("unknown player {}", params.player)))?;
+    if params.reason.trim().is_empty() {
+        return Err(Error::BadRequest("reason must not be empty".to_string()));
+    }
+    let issued_at = Utc::now();
+    if params.expires_at.is_some_and(|expires_at| expires_at <= issued_at) {
+        return Err(Error::BadRequest("expires_at must be in the future".to_string()));
+    }
+    if let Some(match_id) = params.match_id {
+        if matches::Entity::find_by_id(match_id).one(&ctx.db).await?.is_none() {
+            return Err(Error::BadRequest(format!("unknown match {match_id}")));
+        }
+    }
+
+    let item = ActiveModel {
+        player_id: Set(player.id),
+        reason: Set(params.reason.trim().to_string()),
+        issued_by: Set(Some(auth.user.id)),
+        issued_at: Set(issued_at),
+        expires_at: Set(params.expires_at),
+        match_id: Set(params.match_id),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await?;
+    format::json(views(&ctx, vec![item]).await?.pop().ok_or(Error::NotFound)?)
+}
+
+#[debug_handler]
+pub async fn revoke(
+    auth: AdminUser,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let existing = load_item(&ctx, id).await?;
+    if !existing.is_active(Utc::now()) {
+        return Err(Error::BadRequest(format!("penalty {id} is not active")));
+    }
+    let mut item = existing.into_active_model();
+    item.revoked_at = Set(Some(Utc::now()));
+    item.revoked_by = Set(Some(auth.user.id));
+    let item = item.update(&ctx.db).await?;
+    format::json(views(&ctx, vec![item]).await?.pop().ok_or(Error::NotFound)?)
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/penalties")
+        .add("/", get(list_active))
+        .add("/", post(add))
+        .add("/:id/revoke", post(revoke))
+}
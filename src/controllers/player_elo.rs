@@ -1,10 +1,37 @@
 #![allow(clippy::unused_async)]
 use loco_rs::prelude::*;
-use sea_orm::{DbBackend, EntityTrait, Statement};
 use axum::debug_handler;
-use axum::extract::Path;
+use axum::extract::{Path, Query};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
-use crate::models::_entities::player_elo::Entity;
+use crate::models::_entities::player_elo::{Column, Model};
+use crate::models::_entities::players::Model as PlayerModel;
+use crate::models::matches::DateRange;
+use crate::models::player_elo::{self, Bucket, SeriesPoint};
+
+/// Most players one series request can compare.
+const MAX_SERIES_PLAYERS: usize = 10;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SeriesParams {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub bucket: Bucket,
+}
+
+#[derive(Serialize)]
+struct PlayerSeries {
+    player_name: Option<String>,
+    points: Vec<SeriesPoint>,
+}
+
+#[derive(Serialize)]
+struct SeriesResponse {
+    bucket: Bucket,
+    series: Vec<PlayerSeries>,
+}
 
 #[debug_handler]
 pub async fn echo(req_body: String) -> String {
@@ -22,30 +49,72 @@ pub async fn get_player_elo_by_player_name(
     Path(player_name): Path<String>,
     State(ctx): State<AppContext>
 ) -> Result<Response> {
-    println!("Received player_name: {}", player_name);
-    
-    let statement = Statement::from_sql_and_values(
-        DbBackend::MySql,
-        r#"SELECT * FROM player_elo WHERE LOWER(player_name) = LOWER(?) ORDER BY created_at ASC"#,
-        [player_name.clone().into()]
-    );
-    
-    println!("SQL Query: {}, Parameters: {:?}", statement.sql, statement.values);
-    
-    let player_elo = Entity::find()
-        .from_raw_sql(statement)
-        .all(&ctx.db)
-        .await?;
-        
-    println!("Query result: {:?}", player_elo);
-    
+    let player_elo = Model::history_for_name(&ctx.db, &player_name).await?;
+
+    tracing::debug!(player_name, entries = player_elo.len(), "loaded elo history");
+
     format::json(player_elo)
 }
 
+/// Rating history of one or more players, comma separated in the path
+/// (`/api/player_elo/ed,kevin/series`), for comparison charts.
+#[debug_handler]
+pub async fn get_series(
+    Path(player_names): Path<String>,
+    State(ctx): State<AppContext>,
+    Query(params): Query<SeriesParams>,
+) -> Result<Response> {
+    let names = player_names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if names.is_empty() || names.len() > MAX_SERIES_PLAYERS {
+        return Err(Error::BadRequest(format!(
+            "expected between 1 and {MAX_SERIES_PLAYERS} player names"
+        )));
+    }
+
+    let range = DateRange {
+        from: params.from,
+        to: params.to,
+    };
+    let mut series = Vec::with_capacity(names.len());
+    for name in names {
+        let player = PlayerModel::find_by_name(&ctx.db, name)
+            .await?
+            .ok_or(Error::NotFound)?;
+        let history = Model::history_for_player_matching(
+            &ctx.db,
+            &player,
+            range.condition_on(Column::CreatedAt),
+        )
+        .await?;
+        let points = player_elo::bucketed(&history, params.bucket);
+        tracing::debug!(
+            player_name = name,
+            entries = history.len(),
+            points = points.len(),
+            bucket = ?params.bucket,
+            "built elo series"
+        );
+        series.push(PlayerSeries {
+            player_name: player.player_name,
+            points,
+        });
+    }
+
+    format::json(SeriesResponse {
+        bucket: params.bucket,
+        series,
+    })
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("api/player_elo")
         .add("/", get(hello))
         .add("/echo", post(echo))
         .add("/:player_name", get(get_player_elo_by_player_name))
+        .add("/:player_name/series", get(get_series))
 }
@@ -1,69 +1,115 @@
 #![allow(clippy::unused_async)]
 use axum::debug_handler;
+use axum::extract::Query;
 use loco_rs::prelude::*;
-use sea_orm::{DbBackend, EntityTrait, QueryOrder, Statement};
-use serde::Serialize;
+use sea_orm::{EntityTrait, QueryOrder};
+use serde::{Deserialize, Serialize};
 
-use crate::models::_entities::players::{Entity, Column};
-use crate::models::_entities::{matches, player_elo};
+use crate::controllers::guard::{AdminUser, BotUser};
+use crate::models::_entities::players::{Entity, Column, Model};
+use crate::models::players::name_eq;
+use crate::models::_entities::{match_participants, matches, player_elo};
+use crate::models::achievements;
+use crate::models::player_aliases;
+use crate::models::player_search::{self, SearchHit, SearchParams};
+use crate::models::match_player_stats;
+use crate::models::leaderboard::{self, LeaderboardParams};
+use crate::models::player_penalties;
+use crate::models::player_stats::PlayerStats;
+use crate::models::seasons;
+use crate::models::steam_report;
+use crate::steam::{InvalidSteamId, SteamId};
 
 #[derive(Serialize)]
 struct PlayerCombinedData {
-    player: Option<crate::models::_entities::players::Model>,
+    player: PlayerView,
     matches: Vec<matches::Model>,
     elo_history: Vec<player_elo::Model>,
 }
 
+/// A player with whether they are currently dunced, i.e. have a penalty in
+/// force.
+#[derive(Serialize)]
+struct PlayerView {
+    #[serde(flatten)]
+    player: Model,
+    dunced: bool,
+}
+
+async fn view(ctx: &AppContext, player: Option<Model>) -> Result<PlayerView> {
+    let player = player.ok_or(Error::NotFound)?;
+    let dunced = player_penalties::is_dunced(&ctx.db, player.id).await?;
+    Ok(PlayerView { player, dunced })
+}
+
 #[debug_handler]
 pub async fn list(State(ctx): State<AppContext>) -> Result<Response> {
-    format::json(Entity::find().all(&ctx.db).await?)
+    let dunced = player_penalties::dunced_players(&ctx.db).await?;
+    let players = Entity::find()
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|player| PlayerView {
+            dunced: dunced.contains(&player.id),
+            player,
+        })
+        .collect::<Vec<_>>();
+    format::json(players)
 }
 
 #[debug_handler]
-pub async fn get_one(Path(id): Path<u32>, State(ctx): State<AppContext>) -> Result<Response> {
+pub async fn get_one(Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
     let player = Entity::find_by_id(id).one(&ctx.db).await?;
-    match player {
-        Some(p) => format::json(p),
-        None => Err(Error::NotFound),
-    }
+    format::json(view(&ctx, player).await?)
 }
 
 #[debug_handler]
 pub async fn get_by_discord_id(Path(discord_id): Path<String>, State(ctx): State<AppContext>) -> Result<Response> {
     let player = Entity::find().filter(Column::DiscordId.eq(discord_id)).one(&ctx.db).await?;
-    match player {
-        Some(p) => format::json(p),
-        None => Err(Error::NotFound),
-    }
+    format::json(view(&ctx, player).await?)
 }
 
 #[debug_handler]
 pub async fn get_by_name(Path(name): Path<String>, State(ctx): State<AppContext>) -> Result<Response> {
-    let statement = Statement::from_sql_and_values(
-        DbBackend::MySql,
-        r#"SELECT * FROM players WHERE LOWER(player_name) = LOWER(?)"#,
-        [name.clone().into()]
-    );
-    
-    println!("SQL Query: {}, Parameters: {:?}", statement.sql, statement.values);
-    
-    let player = Entity::find()
-        .from_raw_sql(statement)
-        .one(&ctx.db)
-        .await?;
-    match player {
-        Some(p) => format::json(p),
-        None => Err(Error::NotFound),
+    let player = Model::find_by_name(&ctx.db, &name).await?;
+    format::json(view(&ctx, player).await?)
+}
+
+/// Search hits, with whether each player is dunced as in [`PlayerView`].
+#[derive(Serialize)]
+struct SearchView {
+    #[serde(flatten)]
+    hit: SearchHit,
+    dunced: bool,
+}
+
+/// Players by prefix or fuzzy match of their current or former names.
+#[debug_handler]
+pub async fn search(
+    State(ctx): State<AppContext>,
+    Query(params): Query<SearchParams>,
+) -> Result<Response> {
+    if params.q.trim().is_empty() {
+        return Err(Error::BadRequest("q must not be empty".to_string()));
     }
+    let dunced = player_penalties::dunced_players(&ctx.db).await?;
+    let hits = player_search::search(&ctx.db, &params)
+        .await?
+        .into_iter()
+        .map(|hit| SearchView {
+            dunced: dunced.contains(&hit.player.id),
+            hit,
+        })
+        .collect::<Vec<_>>();
+    format::json(hits)
 }
 
 #[debug_handler]
-pub async fn list_by_elo(State(ctx): State<AppContext>) -> Result<Response> {
-    format::json(Entity::find()
-        .filter(Column::DeletedAt.is_null())
-        .order_by_desc(Column::CurrentElo)
-        .all(&ctx.db)
-        .await?)
+pub async fn list_by_elo(
+    State(ctx): State<AppContext>,
+    Query(params): Query<LeaderboardParams>,
+) -> Result<Response> {
+    format::json(leaderboard::leaderboard(&ctx.db, &params).await?)
 }
 
 #[debug_handler]
@@ -72,50 +118,20 @@ pub async fn get_player_combined_data(
     State(ctx): State<AppContext>
 ) -> Result<Response> {
     // Get player data
-    let player_statement = Statement::from_sql_and_values(
-        DbBackend::MySql,
-        r#"SELECT * FROM players WHERE LOWER(player_name) = LOWER(?)"#,
-        [name.clone().into()]
-    );
-    
-    let player = Entity::find()
-        .from_raw_sql(player_statement.clone())
-        .one(&ctx.db)
-        .await?;
+    let player = view(&ctx, Model::find_by_name(&ctx.db, &name).await?).await?;
 
     // Get matches data
-    let player_discord_id = player
-        .as_ref()
-        .map(|p| p.discord_id.clone())
-        .ok_or(Error::NotFound)?;
-
-    let matches_statement = Statement::from_sql_and_values(
-        DbBackend::MySql,
-        r#"SELECT * FROM matches WHERE 
-           FIND_IN_SET(?, blue_team) > 0 OR 
-           FIND_IN_SET(?, red_team) > 0 
-           ORDER BY created_at DESC"#,
-        [player_discord_id.clone().into(), player_discord_id.into()]
-    );
+    let player_id = player.player.id;
 
     let matches = matches::Entity::find()
-        .from_raw_sql(matches_statement)
+        .inner_join(match_participants::Entity)
+        .filter(match_participants::Column::PlayerId.eq(player_id))
+        .order_by_desc(matches::Column::CreatedAt)
         .all(&ctx.db)
         .await?;
 
-    // Get ELO history
-    let elo_statement = Statement::from_sql_and_values(
-        DbBackend::MySql,
-        r#"SELECT * FROM player_elo 
-           WHERE LOWER(player_name) = LOWER(?) 
-           ORDER BY created_at ASC"#,
-        [name.clone().into()]
-    );
-
-    let elo_history = player_elo::Entity::find()
-        .from_raw_sql(elo_statement)
-        .all(&ctx.db)
-        .await?;
+    // Get ELO history; by Discord ID, so entries under former names count
+    let elo_history = player_elo::Model::history_for_player(&ctx.db, &player.player).await?;
 
     // Combine all data
     let combined_data = PlayerCombinedData {
@@ -127,13 +143,203 @@ pub async fn get_player_combined_data(
     format::json(combined_data)
 }
 
+#[derive(Deserialize)]
+pub struct StatsParams {
+    pub season: Option<String>,
+}
+
+#[debug_handler]
+pub async fn get_stats(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+    Query(params): Query<StatsParams>,
+) -> Result<Response> {
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let season = match &params.season {
+        Some(season) => Some(
+            seasons::Model::find_by_name(&ctx.db, season)
+                .await?
+                .ok_or(Error::NotFound)?,
+        ),
+        None => None,
+    };
+    format::json(PlayerStats::compute(&ctx.db, &player, season.as_ref()).await?)
+}
+
+#[debug_handler]
+pub async fn get_achievements(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    format::json(achievements::for_player(&ctx.db, player.id).await?)
+}
+
+#[debug_handler]
+pub async fn get_penalties(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    format::json(player_penalties::Model::history(&ctx.db, player.id).await?)
+}
+
+#[debug_handler]
+pub async fn get_aliases(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    format::json(player_aliases::for_player(&ctx.db, player.id).await?)
+}
+
+#[derive(Deserialize)]
+pub struct RenameParams {
+    pub player_name: String,
+}
+
+/// Renames the player, keeping the old name as an alias. A name can only
+/// be the current name of one player.
+#[debug_handler]
+pub async fn rename(
+    _auth: BotUser,
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<RenameParams>,
+) -> Result<Response> {
+    let new_name = params.player_name.trim();
+    if new_name.is_empty() {
+        return Err(Error::BadRequest("player_name must not be empty".to_string()));
+    }
+    // Checked in the transaction that writes the new name and alias
+    let txn = ctx.db.begin().await?;
+    let player = Model::find_by_name(&txn, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let taken = Entity::find()
+        .filter(name_eq(Column::PlayerName, new_name))
+        .filter(Column::Id.ne(player.id))
+        .one(&txn)
+        .await?;
+    if taken.is_some() {
+        return Err(Error::BadRequest(format!("{new_name} is another player's name")));
+    }
+
+    let mut item = player.into_active_model();
+    item.player_name = Set(Some(new_name.to_string()));
+    let player = item.update(&txn).await?;
+    txn.commit().await?;
+    format::json(view(&ctx, Some(player)).await?)
+}
+
+#[debug_handler]
+pub async fn get_match_stats(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    format::json(match_player_stats::for_player(&ctx.db, player.id).await?)
+}
+
+fn parse_steam_id(value: &str) -> Result<SteamId> {
+    value
+        .parse()
+        .map_err(|err: InvalidSteamId| Error::BadRequest(err.to_string()))
+}
+
+#[debug_handler]
+pub async fn get_by_steam_id(
+    Path(steam_id): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let player = Model::find_by_steam_id(&ctx.db, parse_steam_id(&steam_id)?).await?;
+    format::json(view(&ctx, player).await?)
+}
+
+#[derive(Deserialize)]
+pub struct SteamParams {
+    /// SteamID64, SteamID2 or SteamID3.
+    pub steam_id: String,
+}
+
+/// Links a Steam account to the player, stored as SteamID64. An account
+/// can only be linked to one player.
+#[debug_handler]
+pub async fn link_steam(
+    _auth: BotUser,
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<SteamParams>,
+) -> Result<Response> {
+    let steam_id = parse_steam_id(&params.steam_id)?;
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if let Some(other) = Model::find_by_steam_id(&ctx.db, steam_id).await? {
+        if other.id != player.id {
+            return Err(Error::BadRequest(format!(
+                "steam id {steam_id} is already linked to {}",
+                other.player_name.as_deref().unwrap_or("another player")
+            )));
+        }
+    }
+
+    let mut item = player.into_active_model();
+    item.steam_id = Set(Some(steam_id.to_string()));
+    let player = item.update(&ctx.db).await?;
+    format::json(view(&ctx, Some(player)).await?)
+}
+
+#[debug_handler]
+pub async fn unlink_steam(
+    _auth: BotUser,
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let player = Model::find_by_name(&ctx.db, &name)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let mut item = player.into_active_model();
+    item.steam_id = Set(None);
+    let player = item.update(&ctx.db).await?;
+    format::json(view(&ctx, Some(player)).await?)
+}
+
+#[debug_handler]
+pub async fn get_steam_report(_auth: AdminUser, State(ctx): State<AppContext>) -> Result<Response> {
+    format::json(steam_report::report(&ctx.db).await?)
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("api/players")
         .add("/", get(list))
         .add("/by-elo", get(list_by_elo))
+        .add("/search", get(search))
         .add("/:id", get(get_one))
         .add("/discord/:discord_id", get(get_by_discord_id))
+        .add("/steam/:steam_id", get(get_by_steam_id))
+        .add("/steam-report", get(get_steam_report))
         .add("/name/:name", get(get_by_name))
         .add("/combined/:name", get(get_player_combined_data))
+        // The segment holds a player name; the router needs it to share the
+        // parameter name of `/:id`
+        .add("/:id/stats", get(get_stats))
+        .add("/:id/achievements", get(get_achievements))
+        .add("/:id/penalties", get(get_penalties))
+        .add("/:id/match-stats", get(get_match_stats))
+        .add("/:id/aliases", get(get_aliases))
+        .add("/:id/name", put(rename))
+        .add("/:id/steam", put(link_steam))
+        .add("/:id/steam", delete(unlink_steam))
 }
\ No newline at end of file
@@ -0,0 +1,178 @@
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use axum::routing::patch;
+use loco_rs::prelude::*;
+use sea_orm::{prelude::DateTimeUtc, QueryOrder};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::controllers::guard::AdminUser;
+use crate::elo::EloConfig;
+use crate::models::seasons::{self, ActiveModel, Entity, Model, Standing};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Params {
+    pub name: String,
+    pub starts_at: DateTimeUtc,
+    pub ends_at: Option<DateTimeUtc>,
+    pub starting_rating: Option<i32>,
+}
+
+/// Partial update of a season; absent fields are left unchanged.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Patch {
+    pub name: Option<String>,
+    pub starts_at: Option<DateTimeUtc>,
+    /// `Some(None)` when sent as `null`, which reopens the season.
+    #[serde(default, deserialize_with = "present")]
+    pub ends_at: Option<Option<DateTimeUtc>>,
+    pub starting_rating: Option<i32>,
+}
+
+/// Tells a field sent as `null` apart from one that is absent.
+fn present<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+#[derive(Serialize)]
+struct SeasonSummary {
+    #[serde(flatten)]
+    season: Model,
+    /// First place of the archived standings.
+    champion: Option<Standing>,
+}
+
+#[derive(Serialize)]
+struct Leaderboard {
+    season: Model,
+    archived: bool,
+    standings: Vec<Standing>,
+}
+
+async fn load_item(ctx: &AppContext, name: &str) -> Result<Model> {
+    Model::find_by_name(&ctx.db, name)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+fn check_range(starts_at: DateTimeUtc, ends_at: Option<DateTimeUtc>) -> Result<()> {
+    if ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+        return Err(Error::BadRequest("ends_at must be after starts_at".to_string()));
+    }
+    Ok(())
+}
+
+/// Rejects `name` when a season other than `except` already has it.
+async fn check_name_free(ctx: &AppContext, name: &str, except: Option<i32>) -> Result<()> {
+    match Model::find_by_name(&ctx.db, name).await? {
+        Some(season) if Some(season.id) != except => {
+            Err(Error::BadRequest(format!("season {name} already exists")))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[debug_handler]
+pub async fn list(State(ctx): State<AppContext>) -> Result<Response> {
+    let items = Entity::find()
+        .order_by_desc(seasons::Column::StartsAt)
+        .all(&ctx.db)
+        .await?;
+
+    let mut champions = seasons::champions(&ctx.db).await?;
+    let summaries = items
+        .into_iter()
+        .map(|season| SeasonSummary {
+            champion: champions.remove(&season.id),
+            season,
+        })
+        .collect::<Vec<_>>();
+    format::json(summaries)
+}
+
+#[debug_handler]
+pub async fn current(State(ctx): State<AppContext>) -> Result<Response> {
+    format::json(Model::current(&ctx.db).await?.ok_or(Error::NotFound)?)
+}
+
+#[debug_handler]
+pub async fn get_one(Path(name): Path<String>, State(ctx): State<AppContext>) -> Result<Response> {
+    format::json(load_item(&ctx, &name).await?)
+}
+
+#[debug_handler]
+pub async fn leaderboard(
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let season = load_item(&ctx, &name).await?;
+    let config = EloConfig::from_context(&ctx)?;
+    let standings = season.standings(&ctx.db, &config).await?;
+    format::json(Leaderboard {
+        archived: season.archived_at.is_some(),
+        season,
+        standings,
+    })
+}
+
+#[debug_handler]
+pub async fn add(
+    _auth: AdminUser,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    check_range(params.starts_at, params.ends_at)?;
+    check_name_free(&ctx, &params.name, None).await?;
+    let config = EloConfig::from_context(&ctx)?;
+    let item = ActiveModel {
+        name: Set(params.name),
+        starts_at: Set(params.starts_at),
+        ends_at: Set(params.ends_at),
+        starting_rating: Set(params.starting_rating.unwrap_or(config.starting_rating)),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await?;
+    format::json(item)
+}
+
+#[debug_handler]
+pub async fn update(
+    _auth: AdminUser,
+    Path(name): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(patch): Json<Patch>,
+) -> Result<Response> {
+    let existing = load_item(&ctx, &name).await?;
+    let starts_at = patch.starts_at.unwrap_or(existing.starts_at);
+    let ends_at = patch.ends_at.unwrap_or(existing.ends_at);
+    check_range(starts_at, ends_at)?;
+    if let Some(name) = &patch.name {
+        check_name_free(&ctx, name, Some(existing.id)).await?;
+    }
+
+    let mut item = existing.into_active_model();
+    if let Some(name) = patch.name {
+        item.name = Set(name);
+    }
+    if let Some(starting_rating) = patch.starting_rating {
+        item.starting_rating = Set(starting_rating);
+    }
+    item.starts_at = Set(starts_at);
+    item.ends_at = Set(ends_at);
+    format::json(item.update(&ctx.db).await?)
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/seasons")
+        .add("/", get(list))
+        .add("/", post(add))
+        .add("/current", get(current))
+        .add("/:name", get(get_one))
+        .add("/:name", patch(update))
+        .add("/:name/leaderboard", get(leaderboard))
+}
@@ -0,0 +1,122 @@
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::balance::{self, Constraints, DEFAULT_ALTERNATIVES, MAX_ALTERNATIVES};
+use crate::controllers::guard::BotUser;
+use crate::elo::EloConfig;
+use crate::models::_entities::players::Model;
+
+/// A pickup to balance. Players are given by name or Discord ID, and the
+/// constraints refer to them the same way.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BalanceParams {
+    pub players: Vec<String>,
+    /// Pairs to put on opposite teams.
+    #[serde(default)]
+    pub apart: Vec<(String, String)>,
+    /// Pairs to put on the same team.
+    #[serde(default)]
+    pub together: Vec<(String, String)>,
+    /// Blue and red captain.
+    pub captains: Option<(String, String)>,
+    /// Number of splits to return, best first.
+    pub top_k: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct BalancePlayer {
+    id: i32,
+    player_name: Option<String>,
+    discord_id: Option<String>,
+    /// `current_elo`, or the starting rating for an unrated player.
+    elo: i32,
+}
+
+#[derive(Serialize)]
+struct BalancedSplit {
+    blue: Vec<BalancePlayer>,
+    red: Vec<BalancePlayer>,
+    blue_rank: f64,
+    red_rank: f64,
+    blue_probability: f64,
+    red_probability: f64,
+}
+
+#[debug_handler]
+pub async fn balance(
+    _auth: BotUser,
+    State(ctx): State<AppContext>,
+    Json(params): Json<BalanceParams>,
+) -> Result<Response> {
+    let config = EloConfig::from_context(&ctx)?;
+    let mut players: Vec<BalancePlayer> = Vec::with_capacity(params.players.len());
+    let mut keys: Vec<String> = Vec::with_capacity(params.players.len());
+    for key in &params.players {
+        let player = Model::find_by_name_or_discord_id(&ctx.db, key.trim())
+            .await?
+            .ok_or_else(|| Error::BadRequest(format!("unknown player {key}")))?;
+        if players.iter().any(|p| p.id == player.id) {
+            return Err(Error::BadRequest(format!("player {key} is listed twice")));
+        }
+        keys.push(key.trim().to_lowercase());
+        players.push(BalancePlayer {
+            id: player.id,
+            elo: player.current_elo.unwrap_or(config.starting_rating),
+            player_name: player.player_name,
+            discord_id: player.discord_id,
+        });
+    }
+
+    let index_of = |key: &str| {
+        let needle = key.trim().to_lowercase();
+        keys.iter()
+            .position(|k| *k == needle)
+            .or_else(|| {
+                players.iter().position(|p| {
+                    p.player_name.as_deref().map(str::to_lowercase).as_deref() == Some(&needle)
+                        || p.discord_id.as_deref() == Some(needle.as_str())
+                })
+            })
+            .ok_or_else(|| Error::BadRequest(format!("{key} is not in the pickup")))
+    };
+    let pair = |(a, b): &(String, String)| Ok::<_, Error>((index_of(a)?, index_of(b)?));
+    let constraints = Constraints {
+        apart: params.apart.iter().map(pair).collect::<Result<_>>()?,
+        together: params.together.iter().map(pair).collect::<Result<_>>()?,
+        captains: params.captains.as_ref().map(pair).transpose()?,
+    };
+
+    let ratings = players.iter().map(|p| p.elo).collect::<Vec<_>>();
+    let limit = params
+        .top_k
+        .unwrap_or(DEFAULT_ALTERNATIVES)
+        .min(MAX_ALTERNATIVES);
+    let splits = balance::balance(&ratings, &constraints, limit)
+        .map_err(|err| Error::BadRequest(err.to_string()))?;
+
+    let team = |indexes: &[usize]| {
+        indexes
+            .iter()
+            .map(|&index| players[index].clone())
+            .collect::<Vec<_>>()
+    };
+    format::json(
+        splits
+            .into_iter()
+            .map(|split| BalancedSplit {
+                blue: team(&split.blue),
+                red: team(&split.red),
+                blue_rank: split.blue_rank,
+                red_rank: split.red_rank,
+                blue_probability: split.blue_probability,
+                red_probability: 1.0 - split.blue_probability,
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+pub fn routes() -> Routes {
+    Routes::new().prefix("api/teams").add("/balance", post(balance))
+}
@@ -0,0 +1,410 @@
+//! Elo rating engine that replays the `matches` history.
+//!
+//! Each team is rated by the average rating of its players. Every player on a
+//! team moves by the same amount, `k_factor * (score - expected)`, where a win
+//! scores 1, a draw 0.5 and a loss 0. Matches without an outcome are skipped.
+
+use std::collections::HashMap;
+
+use loco_rs::{app::AppContext, model::ModelResult};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait,
+    EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::{matches, player_elo, players};
+use crate::models::match_participants;
+
+/// Engine tuning, read from the `settings.elo` section of the app config.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EloConfig {
+    /// Largest rating change a single match can cause.
+    pub k_factor: f64,
+    /// Rating of a player before their first match.
+    pub starting_rating: i32,
+}
+
+impl Default for EloConfig {
+    fn default() -> Self {
+        Self {
+            k_factor: 32.0,
+            starting_rating: 1000,
+        }
+    }
+}
+
+impl EloConfig {
+    /// Reads `settings.elo` from the app config, using the defaults for
+    /// anything that is not set.
+    ///
+    /// # Errors
+    ///
+    /// When the `settings.elo` section does not deserialize
+    pub fn from_context(ctx: &AppContext) -> loco_rs::Result<Self> {
+        match ctx.config.settings.as_ref().and_then(|s| s.get("elo")) {
+            Some(elo) => Ok(serde_json::from_value(elo.clone())?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Probability that a side rated `rating` beats a side rated `opponent`.
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Blue's score for a `matches.match_outcome`: 1 for a blue win, 0 for a red
+/// win, 0.5 for a draw and `None` when the match has no result.
+pub fn blue_score(match_outcome: Option<i32>) -> Option<f64> {
+    match match_outcome {
+        Some(0) => Some(0.5),
+        Some(1) => Some(1.0),
+        Some(2) => Some(0.0),
+        _ => None,
+    }
+}
+
+/// Rating of one player before and after a match.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RatingChange {
+    pub player_id: i32,
+    pub before: i32,
+    pub after: i32,
+}
+
+impl RatingChange {
+    pub fn change(&self) -> i32 {
+        self.after - self.before
+    }
+}
+
+/// What the engine computed for one match.
+#[derive(Clone, Debug, Serialize)]
+pub struct TeamRating {
+    pub blue_rank: f64,
+    pub red_rank: f64,
+    pub blue_probability: f64,
+    pub blue: Vec<RatingChange>,
+    pub red: Vec<RatingChange>,
+}
+
+/// Current ratings of every player seen so far.
+#[derive(Clone, Debug)]
+pub struct Engine {
+    config: EloConfig,
+    ratings: HashMap<i32, i32>,
+}
+
+impl Engine {
+    pub fn new(config: EloConfig) -> Self {
+        Self {
+            config,
+            ratings: HashMap::new(),
+        }
+    }
+
+    /// Engine that continues from already known ratings.
+    pub fn with_ratings(config: EloConfig, ratings: HashMap<i32, i32>) -> Self {
+        Self { config, ratings }
+    }
+
+    pub fn rating(&self, player_id: i32) -> i32 {
+        self.ratings
+            .get(&player_id)
+            .copied()
+            .unwrap_or(self.config.starting_rating)
+    }
+
+    pub fn ratings(&self) -> &HashMap<i32, i32> {
+        &self.ratings
+    }
+
+    /// Average rating of a team.
+    pub fn team_rating(&self, player_ids: &[i32]) -> f64 {
+        if player_ids.is_empty() {
+            return f64::from(self.config.starting_rating);
+        }
+        let total: f64 = player_ids.iter().map(|id| f64::from(self.rating(*id))).sum();
+        total / player_ids.len() as f64
+    }
+
+    /// Rates a match and applies the changes. `blue_score` comes from
+    /// [`blue_score`].
+    pub fn rate(&mut self, blue: &[i32], red: &[i32], blue_score: f64) -> TeamRating {
+        let blue_rank = self.team_rating(blue);
+        let red_rank = self.team_rating(red);
+        let blue_probability = expected_score(blue_rank, red_rank);
+
+        let blue_delta = (self.config.k_factor * (blue_score - blue_probability)).round();
+        #[allow(clippy::cast_possible_truncation)]
+        let blue_delta = blue_delta as i32;
+
+        TeamRating {
+            blue_rank,
+            red_rank,
+            blue_probability,
+            blue: self.apply(blue, blue_delta),
+            red: self.apply(red, -blue_delta),
+        }
+    }
+
+    fn apply(&mut self, player_ids: &[i32], delta: i32) -> Vec<RatingChange> {
+        player_ids
+            .iter()
+            .map(|&player_id| {
+                let before = self.rating(player_id);
+                let after = before + delta;
+                self.ratings.insert(player_id, after);
+                RatingChange {
+                    player_id,
+                    before,
+                    after,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A match from the history together with the engine's ratings for it.
+#[derive(Clone, Debug)]
+pub struct RatedMatch {
+    pub stored: matches::Model,
+    pub rating: TeamRating,
+    pub players: HashMap<i32, players::Model>,
+}
+
+impl RatedMatch {
+    /// `player_elo` rows recording every player's rating after this match.
+    pub fn elo_entries(&self) -> Vec<player_elo::ActiveModel> {
+        self.rating
+            .blue
+            .iter()
+            .chain(&self.rating.red)
+            .filter_map(|change| {
+                let player = self.players.get(&change.player_id)?;
+                Some(player_elo::ActiveModel {
+                    match_id: ActiveValue::set(self.stored.match_id.map(i64::from)),
+                    player_name: ActiveValue::set(player.player_name.clone()),
+                    player_elos: ActiveValue::set(Some(change.after)),
+                    discord_id: ActiveValue::set(
+                        player
+                            .discord_id
+                            .as_deref()
+                            .and_then(|id| id.trim().parse().ok()),
+                    ),
+                    created_at: ActiveValue::set(Some(self.stored.created_at)),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// The whole history replayed in order, with the final ratings.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub matches: Vec<RatedMatch>,
+    pub ratings: HashMap<i32, i32>,
+}
+
+/// Replays every decided, non-deleted match in `created_at` order, optionally
+/// limited to one `game_type`.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn replay<C: ConnectionTrait>(
+    db: &C,
+    config: &EloConfig,
+    game_type: Option<&str>,
+) -> ModelResult<Replay> {
+    let mut condition = Condition::all();
+    if let Some(game_type) = game_type {
+        condition = condition.add(matches::Column::GameType.eq(game_type));
+    }
+    replay_matching(db, config, condition).await
+}
+
+/// Replays the decided, non-deleted matches selected by `condition` in
+/// `created_at` order, with every player starting from
+/// `config.starting_rating`.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn replay_matching<C: ConnectionTrait>(
+    db: &C,
+    config: &EloConfig,
+    condition: Condition,
+) -> ModelResult<Replay> {
+    let history = matches::Entity::find()
+        .filter(matches::Column::DeletedAt.is_null())
+        .filter(matches::Column::MatchOutcome.is_in([0, 1, 2]))
+        .filter(condition)
+        .order_by_asc(matches::Column::CreatedAt)
+        .order_by_asc(matches::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut rosters =
+        match_participants::rosters_for(db, history.iter().map(|m| m.id).collect()).await?;
+
+    let mut engine = Engine::new(config.clone());
+    let mut rated = Vec::new();
+    for stored in history {
+        let Some(roster) = rosters.remove(&stored.id) else {
+            continue;
+        };
+        let Some(score) = blue_score(stored.match_outcome) else {
+            continue;
+        };
+        if roster.blue.is_empty() || roster.red.is_empty() {
+            continue;
+        }
+
+        let blue = roster.blue.iter().map(|p| p.id).collect::<Vec<_>>();
+        let red = roster.red.iter().map(|p| p.id).collect::<Vec<_>>();
+        let rating = engine.rate(&blue, &red, score);
+        let players = roster
+            .blue
+            .into_iter()
+            .chain(roster.red)
+            .map(|p| (p.id, p))
+            .collect();
+        rated.push(RatedMatch {
+            stored,
+            rating,
+            players,
+        });
+    }
+
+    Ok(Replay {
+        matches: rated,
+        ratings: engine.ratings().clone(),
+    })
+}
+
+/// `matches` stores ranks and probabilities as `FLOAT`.
+#[allow(clippy::cast_possible_truncation)]
+fn as_stored(value: f64) -> f32 {
+    value as f32
+}
+
+/// Rates one match from its players' stored `current_elo`.
+///
+/// The match's `blue_rank`/`red_rank` and probabilities are set to the
+/// pre-match prediction. When the match has a result, every player also gets
+/// a `player_elo` entry and a new `current_elo`. Returns the updated match and
+/// the entries written.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn record_result<C: ConnectionTrait>(
+    db: &C,
+    config: &EloConfig,
+    stored: matches::Model,
+) -> ModelResult<(matches::Model, Vec<player_elo::Model>)> {
+    let roster = match_participants::rosters_for(db, vec![stored.id])
+        .await?
+        .remove(&stored.id)
+        .unwrap_or_default();
+    let ratings = roster
+        .blue
+        .iter()
+        .chain(&roster.red)
+        .filter_map(|p| Some((p.id, p.current_elo?)))
+        .collect();
+    let mut engine = Engine::with_ratings(config.clone(), ratings);
+
+    let blue = roster.blue.iter().map(|p| p.id).collect::<Vec<_>>();
+    let red = roster.red.iter().map(|p| p.id).collect::<Vec<_>>();
+    let blue_rank = engine.team_rating(&blue);
+    let red_rank = engine.team_rating(&red);
+    let blue_probability = expected_score(blue_rank, red_rank);
+
+    let mut item = stored.into_active_model();
+    item.blue_rank = ActiveValue::set(Some(as_stored(blue_rank)));
+    item.red_rank = ActiveValue::set(Some(as_stored(red_rank)));
+    item.blue_probability = ActiveValue::set(Some(as_stored(blue_probability)));
+    item.red_probability = ActiveValue::set(Some(as_stored(1.0 - blue_probability)));
+    let stored = item.update(db).await?;
+
+    let Some(score) = blue_score(stored.match_outcome) else {
+        return Ok((stored, vec![]));
+    };
+    if blue.is_empty() || red.is_empty() {
+        return Ok((stored, vec![]));
+    }
+
+    let rated = RatedMatch {
+        rating: engine.rate(&blue, &red, score),
+        players: roster
+            .blue
+            .into_iter()
+            .chain(roster.red)
+            .map(|p| (p.id, p))
+            .collect(),
+        stored,
+    };
+    let mut entries = Vec::new();
+    for entry in rated.elo_entries() {
+        entries.push(entry.insert(db).await?);
+    }
+    for change in rated.rating.blue.iter().chain(&rated.rating.red) {
+        players::Entity::update_many()
+            .col_expr(players::Column::CurrentElo, Expr::value(change.after))
+            .filter(players::Column::Id.eq(change.player_id))
+            .exec(db)
+            .await?;
+    }
+
+    Ok((rated.stored, entries))
+}
+
+/// Undoes [`record_result`] for a match: every player's `current_elo` moves
+/// back by the change recorded in their `player_elo` entry for the match, and
+/// the entries are deleted. Ratings of matches played afterwards are not
+/// replayed; run the `recompute_elo` task for that.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn revert_result<C: ConnectionTrait>(
+    db: &C,
+    config: &EloConfig,
+    stored: &matches::Model,
+) -> ModelResult<()> {
+    let Some(match_id) = stored.match_id else {
+        return Ok(());
+    };
+    let entries = player_elo::Entity::find()
+        .filter(player_elo::Column::MatchId.eq(i64::from(match_id)))
+        .all(db)
+        .await?;
+
+    for entry in entries {
+        if let (Some(discord_id), Some(after)) = (entry.discord_id, entry.player_elos) {
+            let before = player_elo::Entity::find()
+                .filter(player_elo::Column::DiscordId.eq(discord_id))
+                .filter(player_elo::Column::EntryId.lt(entry.entry_id))
+                .order_by_desc(player_elo::Column::EntryId)
+                .one(db)
+                .await?
+                .and_then(|previous| previous.player_elos)
+                .unwrap_or(config.starting_rating);
+            players::Entity::update_many()
+                .col_expr(
+                    players::Column::CurrentElo,
+                    Expr::col(players::Column::CurrentElo).sub(after - before),
+                )
+                .filter(players::Column::DiscordId.eq(discord_id.to_string()))
+                .exec(db)
+                .await?;
+        }
+        entry.delete(db).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! String similarity for name search, tolerant of typos and of players
+//! writing only part of a name.
+//!
+//! Both measures compare lowercased strings and return a similarity from 0
+//! (nothing in common) to 1 (equal).
+
+use std::collections::HashSet;
+
+/// Edit distance: the fewest single-character insertions, deletions and
+/// substitutions turning `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Trigrams of a lowercased string padded the way Postgres `pg_trgm` pads
+/// words: two spaces in front and one behind.
+fn trigrams(s: &str) -> HashSet<[char; 3]> {
+    let padded = format!("  {} ", s.to_lowercase())
+        .chars()
+        .collect::<Vec<_>>();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Shared trigrams over all trigrams of both strings, as `pg_trgm`'s
+/// `similarity()`.
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The better of trigram similarity and one minus the edit distance over
+/// the longer length. Trigrams favour shared chunks, while the edit
+/// distance forgives a typo in a short name that breaks most trigrams.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    let edit = 1.0 - levenshtein(&a, &b) as f64 / longest as f64;
+    edit.max(trigram_similarity(&a, &b))
+}
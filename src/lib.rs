@@ -1,6 +1,12 @@
 pub mod app;
+pub mod balance;
 pub mod controllers;
+pub mod elo;
+pub mod fuzzy;
 pub mod initializers;
+pub mod live;
 pub mod models;
+pub mod stats_log;
+pub mod steam;
 pub mod tasks;
 pub mod workers;
\ No newline at end of file
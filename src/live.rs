@@ -0,0 +1,222 @@
+//! In-process feed of match events, streamed by `/api/live`.
+//!
+//! Match ingestion publishes to a broadcast channel and every client gets its
+//! own receiver. The last [`BACKLOG`] events are kept so a client that
+//! reconnects with `Last-Event-ID` receives what it missed. Event ids restart
+//! with the server, and CLI tasks run in their own process, so their changes
+//! (e.g. `recompute_elo`) are not published.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use loco_rs::model::ModelResult;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::models::_entities::{matches, player_elo, players};
+
+/// Events kept for clients resuming with `Last-Event-ID`.
+pub const BACKLOG: usize = 256;
+/// Positions of the leaderboard watched for reorders.
+pub const LEADERBOARD_SIZE: u64 = 20;
+
+/// A leaderboard position in [`LiveEvent::LeaderboardReordered`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Standing {
+    pub position: usize,
+    pub player_id: i32,
+    pub player_name: Option<String>,
+    pub current_elo: Option<i32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// A match was reported, with or without a result.
+    MatchCreated { match_data: matches::Model },
+    /// A match got a result, or its result was corrected.
+    MatchFinished { match_data: matches::Model },
+    /// Ratings moved because of a match.
+    EloChanged {
+        match_id: i32,
+        entries: Vec<player_elo::Model>,
+    },
+    /// The top of the leaderboard is in a new order.
+    LeaderboardReordered { top: Vec<Standing> },
+}
+
+impl LiveEvent {
+    /// The SSE event name.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MatchCreated { .. } => "match_created",
+            Self::MatchFinished { .. } => "match_finished",
+            Self::EloChanged { .. } => "elo_changed",
+            Self::LeaderboardReordered { .. } => "leaderboard_reordered",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    pub id: u64,
+    pub event: LiveEvent,
+}
+
+#[derive(Default)]
+struct Backlog {
+    last_id: u64,
+    events: VecDeque<Envelope>,
+}
+
+pub struct Subscription {
+    pub receiver: broadcast::Receiver<Envelope>,
+    /// Events after the client's last event id, oldest first; `None` when
+    /// some of them are no longer kept and the client has to reload.
+    pub missed: Option<Vec<Envelope>>,
+}
+
+pub struct LiveFeed {
+    sender: broadcast::Sender<Envelope>,
+    backlog: Mutex<Backlog>,
+}
+
+impl Default for LiveFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(BACKLOG).0,
+            backlog: Mutex::default(),
+        }
+    }
+}
+
+impl LiveFeed {
+    /// Publishes `event` to every subscriber, returning its id.
+    pub fn publish(&self, event: LiveEvent) -> u64 {
+        let mut backlog = self.backlog.lock().unwrap_or_else(PoisonError::into_inner);
+        backlog.last_id += 1;
+        let envelope = Envelope {
+            id: backlog.last_id,
+            event,
+        };
+        backlog.events.push_back(envelope.clone());
+        if backlog.events.len() > BACKLOG {
+            backlog.events.pop_front();
+        }
+        // Sent under the lock so a new subscriber sees each event exactly
+        // once, either in `missed` or on its receiver. No subscribers is fine.
+        let _ = self.sender.send(envelope);
+        backlog.last_id
+    }
+
+    /// Subscribes to events published from now on, together with the ones
+    /// after `last_event_id` when resuming.
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> Subscription {
+        let backlog = self.backlog.lock().unwrap_or_else(PoisonError::into_inner);
+        let missed = match last_event_id {
+            None => Some(vec![]),
+            // From before a restart
+            Some(last) if last > backlog.last_id => None,
+            Some(last) => {
+                let oldest_kept = backlog
+                    .events
+                    .front()
+                    .map_or(backlog.last_id + 1, |e| e.id);
+                (last + 1 >= oldest_kept).then(|| {
+                    backlog
+                        .events
+                        .iter()
+                        .filter(|e| e.id > last)
+                        .cloned()
+                        .collect()
+                })
+            }
+        };
+        Subscription {
+            receiver: self.sender.subscribe(),
+            missed,
+        }
+    }
+}
+
+/// The feed of this server process.
+pub fn feed() -> &'static LiveFeed {
+    static FEED: OnceLock<LiveFeed> = OnceLock::new();
+    FEED.get_or_init(LiveFeed::default)
+}
+
+/// The top [`LEADERBOARD_SIZE`] players by `current_elo`, as on the
+/// leaderboard.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn standings<C: ConnectionTrait>(db: &C) -> ModelResult<Vec<Standing>> {
+    Ok(players::Entity::find()
+        .filter(players::Column::DeletedAt.is_null())
+        .order_by_desc(players::Column::CurrentElo)
+        .order_by_asc(players::Column::Id)
+        .limit(LEADERBOARD_SIZE)
+        .all(db)
+        .await?
+        .into_iter()
+        .enumerate()
+        .map(|(index, player)| Standing {
+            position: index + 1,
+            player_id: player.id,
+            player_name: player.player_name,
+            current_elo: player.current_elo,
+        })
+        .collect())
+}
+
+/// What reporting or correcting a match changed, published once the change
+/// is committed.
+pub struct MatchReport {
+    pub match_data: matches::Model,
+    pub elo_entries: Vec<player_elo::Model>,
+    /// Whether the match was reported now rather than corrected.
+    pub created: bool,
+    /// `match_outcome` before a correction.
+    pub previous_outcome: Option<i32>,
+    /// [`standings`] before the change.
+    pub standings_before: Vec<Standing>,
+}
+
+impl MatchReport {
+    /// Publishes the report's events. Reading the new standings can fail
+    /// after the change is committed, so failures are logged, not returned.
+    pub async fn publish<C: ConnectionTrait>(self, db: &C) {
+        let feed = feed();
+        if self.created {
+            feed.publish(LiveEvent::MatchCreated {
+                match_data: self.match_data.clone(),
+            });
+        }
+        if self.match_data.match_outcome.is_some()
+            && (self.created || self.match_data.match_outcome != self.previous_outcome)
+        {
+            feed.publish(LiveEvent::MatchFinished {
+                match_data: self.match_data.clone(),
+            });
+        }
+        if self.elo_entries.is_empty() {
+            return;
+        }
+        feed.publish(LiveEvent::EloChanged {
+            match_id: self.match_data.id,
+            entries: self.elo_entries,
+        });
+
+        match standings(db).await {
+            Ok(top) => {
+                let order = |s: &[Standing]| s.iter().map(|s| s.player_id).collect::<Vec<_>>();
+                if order(&top) != order(&self.standings_before) {
+                    feed.publish(LiveEvent::LeaderboardReordered { top });
+                }
+            }
+            Err(err) => tracing::warn!(error = %err, "could not read standings for the live feed"),
+        }
+    }
+}
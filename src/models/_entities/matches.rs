@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 #[sea_orm(table_name = "matches")]
 pub struct Model {
     #[sea_orm(primary_key)]
-    pub id: u32,
+    pub id: i32,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
     pub match_id: Option<i32>,
@@ -35,4 +35,13 @@ pub struct Model {
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::match_participants::Entity")]
+    MatchParticipants,
+}
+
+impl Related<super::match_participants::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MatchParticipants.def()
+    }
+}
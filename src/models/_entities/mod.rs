@@ -1,7 +1,17 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.1
 
 pub mod prelude;
+pub mod achievements;
+pub mod match_participants;
+pub mod match_player_stats;
 pub mod matches;
 pub mod notes;
+pub mod player_achievements;
+pub mod player_aliases;
 pub mod player_elo;
+pub mod player_penalties;
 pub mod players;
+pub mod season_standings;
+pub mod seasons;
+pub mod stats_snapshots;
+pub mod users;
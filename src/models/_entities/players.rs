@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 #[sea_orm(table_name = "players")]
 pub struct Model {
     #[sea_orm(primary_key)]
-    pub id: u32,
+    pub id: i32,
     pub discord_id: Option<String>,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
@@ -21,12 +21,19 @@ pub struct Model {
     pub dm_wins: Option<i32>,
     pub dm_losses: Option<i32>,
     #[sea_orm(column_type = "Text", nullable)]
-    pub achievements: Option<String>,
-    #[sea_orm(column_type = "Text", nullable)]
     pub dunce: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub steam_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::match_participants::Entity")]
+    MatchParticipants,
+}
+
+impl Related<super::match_participants::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MatchParticipants.def()
+    }
+}
@@ -1,6 +1,16 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.1
 
+pub use super::achievements::Entity as Achievements;
+pub use super::match_participants::Entity as MatchParticipants;
+pub use super::match_player_stats::Entity as MatchPlayerStats;
 pub use super::matches::Entity as Matches;
 pub use super::notes::Entity as Notes;
+pub use super::player_achievements::Entity as PlayerAchievements;
+pub use super::player_aliases::Entity as PlayerAliases;
 pub use super::player_elo::Entity as PlayerElo;
-pub use super::players::Entity as Players;
\ No newline at end of file
+pub use super::player_penalties::Entity as PlayerPenalties;
+pub use super::players::Entity as Players;
+pub use super::season_standings::Entity as SeasonStandings;
+pub use super::seasons::Entity as Seasons;
+pub use super::stats_snapshots::Entity as StatsSnapshots;
+pub use super::users::Entity as Users;
@@ -0,0 +1,313 @@
+//! Achievements awarded from the match history. The definitions live in
+//! [`DEFINITIONS`] and are copied to the `achievements` table by [`sync`],
+//! and [`evaluate`] replays the history to award what players earned.
+
+use std::collections::{HashMap, HashSet};
+
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel, QueryOrder, QuerySelect};
+use serde::Serialize;
+
+pub use super::_entities::achievements::{ActiveModel, Column, Entity, Model};
+use super::_entities::{match_participants, matches, player_achievements};
+use super::matches::MatchResult;
+use super::player_stats::{StreakKind, Streaks};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// What a player has to do to unlock an achievement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Play this many decided matches.
+    Games(usize),
+    /// Win this many matches.
+    Wins(usize),
+    /// Win this many matches in a row.
+    WinStreak(usize),
+    /// Win at least once on every map in the match history.
+    EveryMap,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Definition {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub icon: &'static str,
+    pub rule: Rule,
+}
+
+pub const DEFINITIONS: [Definition; 7] = [
+    Definition {
+        key: "first_win",
+        name: "First Win",
+        description: "Win a match",
+        icon: "trophy",
+        rule: Rule::Wins(1),
+    },
+    Definition {
+        key: "games_10",
+        name: "Regular",
+        description: "Play 10 matches",
+        icon: "calendar",
+        rule: Rule::Games(10),
+    },
+    Definition {
+        key: "games_100",
+        name: "Centurion",
+        description: "Play 100 matches",
+        icon: "shield",
+        rule: Rule::Games(100),
+    },
+    Definition {
+        key: "wins_50",
+        name: "Veteran",
+        description: "Win 50 matches",
+        icon: "medal",
+        rule: Rule::Wins(50),
+    },
+    Definition {
+        key: "win_streak_5",
+        name: "On Fire",
+        description: "Win 5 matches in a row",
+        icon: "flame",
+        rule: Rule::WinStreak(5),
+    },
+    Definition {
+        key: "win_streak_10",
+        name: "Unstoppable",
+        description: "Win 10 matches in a row",
+        icon: "bolt",
+        rule: Rule::WinStreak(10),
+    },
+    Definition {
+        key: "every_map",
+        name: "Cartographer",
+        description: "Win on every map that has been played",
+        icon: "map",
+        rule: Rule::EveryMap,
+    },
+];
+
+/// An achievement with the number of players who unlocked it.
+#[derive(Clone, Debug, Serialize)]
+pub struct AchievementSummary {
+    #[serde(flatten)]
+    pub achievement: Model,
+    pub unlocked_by: usize,
+}
+
+/// An achievement a player unlocked, and the match that unlocked it.
+#[derive(Clone, Debug, Serialize)]
+pub struct Unlocked {
+    #[serde(flatten)]
+    pub achievement: Model,
+    pub unlocked_at: DateTimeUtc,
+    pub match_id: Option<i32>,
+}
+
+/// A player's progress through the match history.
+#[derive(Default)]
+struct Progress {
+    games: usize,
+    wins: usize,
+    streaks: Streaks,
+    maps_won: HashSet<String>,
+}
+
+impl Progress {
+    fn add(&mut self, result: MatchResult, map: Option<&str>) {
+        if result != MatchResult::Undecided {
+            self.games += 1;
+        }
+        if result == MatchResult::Win {
+            self.wins += 1;
+            if let Some(map) = map {
+                self.maps_won.insert(map.to_string());
+            }
+        }
+        self.streaks.add(result);
+    }
+
+    fn meets(&self, rule: Rule, maps: &HashSet<String>) -> bool {
+        match rule {
+            Rule::Games(games) => self.games >= games,
+            Rule::Wins(wins) => self.wins >= wins,
+            Rule::WinStreak(length) => self
+                .streaks
+                .current
+                .is_some_and(|streak| streak.kind == StreakKind::Win && streak.length >= length),
+            Rule::EveryMap => !maps.is_empty() && maps.is_subset(&self.maps_won),
+        }
+    }
+}
+
+/// Writes [`DEFINITIONS`] to the `achievements` table, adding new ones and
+/// updating changed ones, and returns the rows by key.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn sync<C: ConnectionTrait>(db: &C) -> ModelResult<HashMap<&'static str, Model>> {
+    let mut existing: HashMap<String, Model> = Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| (row.key.clone(), row))
+        .collect();
+
+    let mut rows = HashMap::new();
+    for definition in &DEFINITIONS {
+        let row = match existing.remove(definition.key) {
+            Some(row)
+                if row.name == definition.name
+                    && row.description == definition.description
+                    && row.icon.as_deref() == Some(definition.icon) =>
+            {
+                row
+            }
+            Some(row) => {
+                let mut row = row.into_active_model();
+                row.name = ActiveValue::set(definition.name.to_string());
+                row.description = ActiveValue::set(definition.description.to_string());
+                row.icon = ActiveValue::set(Some(definition.icon.to_string()));
+                row.update(db).await?
+            }
+            None => {
+                ActiveModel {
+                    key: ActiveValue::set(definition.key.to_string()),
+                    name: ActiveValue::set(definition.name.to_string()),
+                    description: ActiveValue::set(definition.description.to_string()),
+                    icon: ActiveValue::set(Some(definition.icon.to_string())),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?
+            }
+        };
+        rows.insert(definition.key, row);
+    }
+    Ok(rows)
+}
+
+/// Replays the non-deleted match history and awards every achievement a
+/// player earned but does not have yet, dated to the match that earned it.
+/// Running it again awards nothing new. Returns the number awarded.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn evaluate<C: ConnectionTrait>(db: &C) -> ModelResult<usize> {
+    let definitions = sync(db).await?;
+    let mut unlocked: HashSet<(i32, i32)> = player_achievements::Entity::find()
+        .select_only()
+        .column(player_achievements::Column::PlayerId)
+        .column(player_achievements::Column::AchievementId)
+        .into_tuple::<(i32, i32)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let played = match_participants::Entity::find()
+        .find_also_related(matches::Entity)
+        .filter(matches::Column::DeletedAt.is_null())
+        .order_by_asc(matches::Column::CreatedAt)
+        .order_by_asc(matches::Column::Id)
+        .order_by_asc(match_participants::Column::Id)
+        .all(db)
+        .await?;
+    // Maps played so far, so `EveryMap` asks for the maps of its time
+    let mut maps: HashSet<String> = HashSet::new();
+    let mut progress: HashMap<i32, Progress> = HashMap::new();
+    let mut awards = Vec::new();
+    for (participant, item) in played {
+        let Some(item) = item else {
+            continue;
+        };
+        if let Some(map) = &item.map {
+            maps.insert(map.clone());
+        }
+        let player = progress.entry(participant.player_id).or_default();
+        player.add(item.result_for(&participant.team), item.map.as_deref());
+
+        for definition in &DEFINITIONS {
+            let achievement_id = definitions[definition.key].id;
+            if unlocked.contains(&(participant.player_id, achievement_id))
+                || !player.meets(definition.rule, &maps)
+            {
+                continue;
+            }
+            unlocked.insert((participant.player_id, achievement_id));
+            awards.push(player_achievements::ActiveModel {
+                player_id: ActiveValue::set(participant.player_id),
+                achievement_id: ActiveValue::set(achievement_id),
+                match_id: ActiveValue::set(Some(item.id)),
+                unlocked_at: ActiveValue::set(item.created_at),
+                ..Default::default()
+            });
+        }
+    }
+
+    for chunk in awards.chunks(500) {
+        player_achievements::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await?;
+    }
+    Ok(awards.len())
+}
+
+/// Every achievement with how many players unlocked it, oldest first.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn summaries<C: ConnectionTrait>(db: &C) -> ModelResult<Vec<AchievementSummary>> {
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for achievement_id in player_achievements::Entity::find()
+        .select_only()
+        .column(player_achievements::Column::AchievementId)
+        .into_tuple::<i32>()
+        .all(db)
+        .await?
+    {
+        *counts.entry(achievement_id).or_default() += 1;
+    }
+
+    Ok(Entity::find()
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|achievement| AchievementSummary {
+            unlocked_by: counts.get(&achievement.id).copied().unwrap_or_default(),
+            achievement,
+        })
+        .collect())
+}
+
+/// The achievements `player_id` unlocked, earliest first.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn for_player<C: ConnectionTrait>(db: &C, player_id: i32) -> ModelResult<Vec<Unlocked>> {
+    Ok(player_achievements::Entity::find()
+        .filter(player_achievements::Column::PlayerId.eq(player_id))
+        .find_also_related(Entity)
+        .order_by_asc(player_achievements::Column::UnlockedAt)
+        .order_by_asc(player_achievements::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(unlock, achievement)| {
+            Some(Unlocked {
+                achievement: achievement?,
+                unlocked_at: unlock.unlocked_at,
+                match_id: unlock.match_id,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,189 @@
+//! Calibration of the stored win probabilities: how often blue actually won
+//! when the rating system gave them a given chance.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, Condition, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::matches;
+use super::matches::{DateRange, PredictionRecord};
+use crate::elo;
+
+pub const DEFAULT_BUCKETS: usize = 10;
+pub const MAX_BUCKETS: usize = 50;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Week,
+    #[default]
+    Month,
+}
+
+impl Period {
+    /// Label of the period `at` falls in, e.g. `2024-W40` or `2024-10`.
+    pub fn label(self, at: DateTimeUtc) -> String {
+        match self {
+            Self::Week => {
+                let week = at.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Self::Month => format!("{}-{:02}", at.year(), at.month()),
+        }
+    }
+}
+
+/// Query params of the calibration report.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CalibrationParams {
+    pub game_type: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Number of equal-width probability buckets.
+    pub buckets: Option<usize>,
+    /// Grouping of the scores over time.
+    #[serde(default)]
+    pub period: Period,
+}
+
+impl CalibrationParams {
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::all()
+            .add(matches::Column::DeletedAt.is_null())
+            .add(matches::Column::BlueProbability.is_not_null())
+            .add(
+                DateRange {
+                    from: self.from,
+                    to: self.to,
+                }
+                .condition(),
+            );
+        if let Some(game_type) = &self.game_type {
+            condition = condition.add(matches::Column::GameType.eq(game_type.as_str()));
+        }
+        condition
+    }
+}
+
+/// One bar of the reliability diagram: decided matches whose
+/// `blue_probability` fell in `[lower, upper)`, the last bucket including 1.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ReliabilityBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub matches: usize,
+    /// Mean `blue_probability` of the bucket.
+    pub mean_predicted: Option<f64>,
+    /// Mean blue score of the bucket, a draw counting as half a win.
+    pub observed: Option<f64>,
+    #[serde(skip)]
+    predicted_total: f64,
+    #[serde(skip)]
+    observed_total: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MapCalibration {
+    pub map: String,
+    #[serde(flatten)]
+    pub record: PredictionRecord,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PeriodCalibration {
+    pub period: String,
+    #[serde(flatten)]
+    pub record: PredictionRecord,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Calibration {
+    pub overall: PredictionRecord,
+    pub buckets: Vec<ReliabilityBucket>,
+    /// Most rated maps first.
+    pub by_map: Vec<MapCalibration>,
+    /// Oldest period first.
+    pub by_period: Vec<PeriodCalibration>,
+}
+
+/// Compares the `blue_probability` of every decided match against its
+/// outcome, overall, per probability bucket, per map and per period.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn report<C: ConnectionTrait>(
+    db: &C,
+    params: &CalibrationParams,
+) -> ModelResult<Calibration> {
+    let rated = matches::Entity::find()
+        .filter(params.condition())
+        .order_by_asc(matches::Column::CreatedAt)
+        .order_by_asc(matches::Column::Id)
+        .all(db)
+        .await?;
+    Ok(calibrate(&rated, params))
+}
+
+fn calibrate(rated: &[matches::Model], params: &CalibrationParams) -> Calibration {
+    let count = params.buckets.unwrap_or(DEFAULT_BUCKETS).clamp(1, MAX_BUCKETS);
+    let width = 1.0 / count as f64;
+    let mut buckets = (0..count)
+        .map(|index| ReliabilityBucket {
+            lower: index as f64 * width,
+            upper: (index + 1) as f64 * width,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let mut overall = PredictionRecord::default();
+    let mut maps: BTreeMap<String, PredictionRecord> = BTreeMap::new();
+    let mut periods: BTreeMap<String, PredictionRecord> = BTreeMap::new();
+    for item in rated {
+        let (Some(probability), Some(score)) = (
+            item.blue_probability.map(f64::from),
+            elo::blue_score(item.match_outcome),
+        ) else {
+            continue;
+        };
+        overall.add(item);
+        maps.entry(item.map.clone().unwrap_or_else(|| "unknown".to_string()))
+            .or_default()
+            .add(item);
+        periods
+            .entry(params.period.label(item.created_at))
+            .or_default()
+            .add(item);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let index = ((probability.clamp(0.0, 1.0) / width) as usize).min(count - 1);
+        let bucket = &mut buckets[index];
+        bucket.matches += 1;
+        bucket.predicted_total += probability;
+        bucket.observed_total += score;
+    }
+    for bucket in &mut buckets {
+        if bucket.matches > 0 {
+            bucket.mean_predicted = Some(bucket.predicted_total / bucket.matches as f64);
+            bucket.observed = Some(bucket.observed_total / bucket.matches as f64);
+        }
+    }
+
+    let mut by_map = maps
+        .into_iter()
+        .map(|(map, record)| MapCalibration { map, record })
+        .collect::<Vec<_>>();
+    by_map.sort_by_key(|m| std::cmp::Reverse(m.record.rated));
+    Calibration {
+        overall,
+        buckets,
+        by_map,
+        by_period: periods
+            .into_iter()
+            .map(|(period, record)| PeriodCalibration { period, record })
+            .collect(),
+    }
+}
@@ -0,0 +1,145 @@
+//! The all-time leaderboard by `players.current_elo`, with activity filters.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::{match_participants, matches, players};
+use super::player_elo;
+
+/// Query params of the leaderboard.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LeaderboardParams {
+    /// Minimum decided games played.
+    pub min_games: Option<usize>,
+    /// Only players with a match in the last `active_days` days.
+    pub active_days: Option<i64>,
+    /// Count games and activity in this game type only.
+    pub game_type: Option<String>,
+    /// Compare positions against the standings at the start of this UTC day.
+    pub since: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LeaderboardRow {
+    #[serde(flatten)]
+    pub player: players::Model,
+    pub position: usize,
+    pub rank_tier: String,
+    pub games_played: usize,
+    pub last_played: Option<DateTimeUtc>,
+    /// Position among the same players by their rating at `since`; `None`
+    /// when no `since` was given or the player had no rating then.
+    pub previous_position: Option<usize>,
+    /// Places gained since `since`, negative when the player dropped.
+    pub position_change: Option<i64>,
+}
+
+#[derive(Default)]
+struct Activity {
+    games_played: usize,
+    last_played: Option<DateTimeUtc>,
+}
+
+/// Non-deleted players by `current_elo`, highest first, filtered by `params`.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn leaderboard<C: ConnectionTrait>(
+    db: &C,
+    params: &LeaderboardParams,
+) -> ModelResult<Vec<LeaderboardRow>> {
+    let ranked = players::Entity::find()
+        .filter(players::Column::DeletedAt.is_null())
+        .order_by_desc(players::Column::CurrentElo)
+        .order_by_asc(players::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut query = match_participants::Entity::find()
+        .select_only()
+        .column(match_participants::Column::PlayerId)
+        .column(matches::Column::CreatedAt)
+        .column(matches::Column::MatchOutcome)
+        .inner_join(matches::Entity)
+        .filter(matches::Column::DeletedAt.is_null());
+    if let Some(game_type) = &params.game_type {
+        query = query.filter(matches::Column::GameType.eq(game_type.as_str()));
+    }
+    let mut activity: HashMap<i32, Activity> = HashMap::new();
+    for (player_id, created_at, outcome) in query
+        .into_tuple::<(i32, DateTimeUtc, Option<i32>)>()
+        .all(db)
+        .await?
+    {
+        let entry = activity.entry(player_id).or_default();
+        if matches!(outcome, Some(0..=2)) {
+            entry.games_played += 1;
+        }
+        entry.last_played = entry.last_played.max(Some(created_at));
+    }
+
+    let active_since = params.active_days.map(|days| Utc::now() - Duration::days(days));
+    let mut rows = ranked
+        .into_iter()
+        .filter_map(|player| {
+            let Activity {
+                games_played,
+                last_played,
+            } = activity.remove(&player.id).unwrap_or_default();
+            if params.min_games.is_some_and(|min| games_played < min) {
+                return None;
+            }
+            if let Some(active_since) = active_since {
+                if last_played.is_none_or(|last| last < active_since) {
+                    return None;
+                }
+            }
+            // Without a game type filter everyone stays listed, as before
+            if params.game_type.is_some() && last_played.is_none() {
+                return None;
+            }
+            Some(LeaderboardRow {
+                position: 0,
+                rank_tier: player.rank_tier(),
+                games_played,
+                last_played,
+                previous_position: None,
+                position_change: None,
+                player,
+            })
+        })
+        .collect::<Vec<_>>();
+    for (index, row) in rows.iter_mut().enumerate() {
+        row.position = index + 1;
+    }
+
+    if let Some(since) = params.since {
+        let at = since.and_time(NaiveTime::MIN).and_utc();
+        let ratings = player_elo::ratings_at(db, at).await?;
+        let mut previous = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(index, row)| {
+                let discord_id = row.player.discord_id.as_deref()?.trim().parse::<i64>().ok()?;
+                Some((index, *ratings.get(&discord_id)?))
+            })
+            .collect::<Vec<_>>();
+        previous.sort_by(|(a_index, a), (b_index, b)| b.cmp(a).then(a_index.cmp(b_index)));
+        for (previous_index, (index, _)) in previous.into_iter().enumerate() {
+            let row = &mut rows[index];
+            let previous_position = previous_index + 1;
+            row.previous_position = Some(previous_position);
+            row.position_change = Some(
+                i64::try_from(previous_position).unwrap_or(i64::MAX)
+                    - i64::try_from(row.position).unwrap_or(i64::MAX),
+            );
+        }
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,190 @@
+//! Per-map aggregates of the `matches` history.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, Condition, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::{match_participants, matches, players};
+use super::matches::{DateRange, OutcomeSplit, PredictionRecord, Record};
+use super::players::name_eq;
+
+pub const DEFAULT_MIN_GAMES: usize = 5;
+pub const TOP_PLAYERS: usize = 10;
+
+/// Query params of the map endpoints.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct MapParams {
+    pub game_type: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Games a player needs on the map to be ranked in `top_players`.
+    pub min_games: Option<usize>,
+}
+
+impl MapParams {
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::all()
+            .add(matches::Column::DeletedAt.is_null())
+            .add(matches::Column::Map.is_not_null())
+            .add(
+                DateRange {
+                    from: self.from,
+                    to: self.to,
+                }
+                .condition(),
+            );
+        if let Some(game_type) = &self.game_type {
+            condition = condition.add(matches::Column::GameType.eq(game_type.as_str()));
+        }
+        condition
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MapSummary {
+    pub map: String,
+    #[serde(flatten)]
+    pub outcomes: OutcomeSplit,
+    pub prediction: PredictionRecord,
+}
+
+impl MapSummary {
+    fn add(&mut self, item: &matches::Model) {
+        self.outcomes.add(item);
+        self.prediction.add(item);
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MapPlayer {
+    pub player_id: i32,
+    pub player_name: Option<String>,
+    #[serde(flatten)]
+    pub record: Record,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MapDetail {
+    #[serde(flatten)]
+    pub summary: MapSummary,
+    pub min_games: usize,
+    pub top_players: Vec<MapPlayer>,
+}
+
+/// Summaries of every map played, most played first. Spellings differing
+/// only in case are one map, named as first seen.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn summaries<C: ConnectionTrait>(
+    db: &C,
+    params: &MapParams,
+) -> ModelResult<Vec<MapSummary>> {
+    let mut maps: BTreeMap<String, MapSummary> = BTreeMap::new();
+    for item in matches::Entity::find()
+        .filter(params.condition())
+        .order_by_asc(matches::Column::Id)
+        .all(db)
+        .await?
+    {
+        let Some(map) = item.map.clone() else {
+            continue;
+        };
+        maps.entry(map.to_lowercase())
+            .or_insert_with(|| MapSummary {
+                map,
+                ..Default::default()
+            })
+            .add(&item);
+    }
+
+    let mut summaries = maps.into_values().collect::<Vec<_>>();
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.outcomes.times_played));
+    Ok(summaries)
+}
+
+/// Summary of one map, matched ignoring case, with its best players by
+/// winrate. `None` when the map was never played.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn detail<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+    params: &MapParams,
+) -> ModelResult<Option<MapDetail>> {
+    let played = matches::Entity::find()
+        .filter(params.condition())
+        .filter(name_eq(matches::Column::Map, name))
+        .all(db)
+        .await?;
+    let Some(first) = played.first() else {
+        return Ok(None);
+    };
+
+    let mut summary = MapSummary {
+        map: first.map.clone().unwrap_or_default(),
+        ..Default::default()
+    };
+    for item in &played {
+        summary.add(item);
+    }
+
+    let by_id: HashMap<i32, &matches::Model> = played.iter().map(|m| (m.id, m)).collect();
+    let mut records: HashMap<i32, Record> = HashMap::new();
+    for ids in by_id.keys().copied().collect::<Vec<_>>().chunks(1000) {
+        let participants = match_participants::Entity::find()
+            .filter(match_participants::Column::MatchId.is_in(ids.iter().copied()))
+            .all(db)
+            .await?;
+        for participant in participants {
+            let Some(item) = by_id.get(&participant.match_id) else {
+                continue;
+            };
+            records
+                .entry(participant.player_id)
+                .or_default()
+                .add(item.result_for(&participant.team));
+        }
+    }
+
+    let min_games = params.min_games.unwrap_or(DEFAULT_MIN_GAMES);
+    let mut ranked = records
+        .into_iter()
+        .filter(|(_, record)| record.games_played >= min_games.max(1))
+        .collect::<Vec<_>>();
+    ranked.sort_by(|(a_id, a), (b_id, b)| {
+        b.winrate
+            .total_cmp(&a.winrate)
+            .then(b.games_played.cmp(&a.games_played))
+            .then(a_id.cmp(b_id))
+    });
+    ranked.truncate(TOP_PLAYERS);
+
+    let names: HashMap<i32, Option<String>> = players::Entity::find()
+        .filter(players::Column::Id.is_in(ranked.iter().map(|(id, _)| *id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.player_name))
+        .collect();
+    let top_players = ranked
+        .into_iter()
+        .map(|(player_id, record)| MapPlayer {
+            player_id,
+            player_name: names.get(&player_id).cloned().flatten(),
+            record,
+        })
+        .collect();
+
+    Ok(Some(MapDetail {
+        summary,
+        min_games,
+        top_players,
+    }))
+}
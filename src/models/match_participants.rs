@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, QueryOrder};
+use serde::Serialize;
+
+pub use super::_entities::match_participants::{ActiveModel, Column, Entity, Model};
+use super::_entities::{matches, players};
+
+pub const BLUE_TEAM: &str = "blue";
+pub const RED_TEAM: &str = "red";
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// Splits a comma-joined roster as stored in `matches.blue_team`/`red_team`
+/// into its Discord IDs.
+pub fn roster_ids(roster: Option<&str>) -> Vec<&str> {
+    roster
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Resolved players of both teams of a match, in slot order.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Rosters {
+    pub blue: Vec<players::Model>,
+    pub red: Vec<players::Model>,
+}
+
+/// A match two players both took part in, with the team each one was on.
+#[derive(Clone, Debug)]
+pub struct SharedMatch {
+    pub match_id: i32,
+    pub first_team: String,
+    pub second_team: String,
+}
+
+impl SharedMatch {
+    pub fn same_team(&self) -> bool {
+        self.first_team == self.second_team
+    }
+}
+
+impl Model {
+    /// The `matches.match_outcome` value that counts as a win for this
+    /// participant's team.
+    pub fn winning_outcome(&self) -> i32 {
+        winning_outcome(&self.team)
+    }
+}
+
+/// The `matches.match_outcome` value that counts as a win for `team`
+/// (1 for blue, 2 for red).
+pub fn winning_outcome(team: &str) -> i32 {
+    if team == BLUE_TEAM {
+        1
+    } else {
+        2
+    }
+}
+
+impl ActiveModel {
+    /// Replaces the participants of a match with the players named in its
+    /// roster strings. Discord IDs without a player row are skipped.
+    ///
+    /// # Errors
+    ///
+    /// When could not query or write to the database
+    pub async fn sync_for_match<C: ConnectionTrait>(
+        db: &C,
+        match_item: &matches::Model,
+    ) -> ModelResult<Vec<Model>> {
+        Entity::delete_many()
+            .filter(Column::MatchId.eq(match_item.id))
+            .exec(db)
+            .await?;
+
+        let rosters = [
+            (BLUE_TEAM, roster_ids(match_item.blue_team.as_deref())),
+            (RED_TEAM, roster_ids(match_item.red_team.as_deref())),
+        ];
+        let discord_ids = rosters
+            .iter()
+            .flat_map(|(_, ids)| ids.iter().map(ToString::to_string))
+            .collect::<Vec<_>>();
+        let player_ids: HashMap<String, i32> = players::Entity::find()
+            .filter(players::Column::DiscordId.is_in(discord_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|p| p.discord_id.map(|id| (id, p.id)))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut participants = Vec::new();
+        for (team, ids) in rosters {
+            for (slot, discord_id) in ids.into_iter().enumerate() {
+                let Some(&player_id) = player_ids.get(discord_id) else {
+                    continue;
+                };
+                if !seen.insert(player_id) {
+                    continue;
+                }
+                let participant = Self {
+                    match_id: ActiveValue::set(match_item.id),
+                    player_id: ActiveValue::set(player_id),
+                    team: ActiveValue::set(team.to_string()),
+                    slot: ActiveValue::set(i32::try_from(slot).unwrap_or(i32::MAX)),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?;
+                participants.push(participant);
+            }
+        }
+
+        Ok(participants)
+    }
+}
+
+/// Loads the resolved rosters of the given matches, keyed by match id.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn rosters_for<C: ConnectionTrait>(
+    db: &C,
+    match_ids: Vec<i32>,
+) -> ModelResult<HashMap<i32, Rosters>> {
+    let mut rows = Vec::new();
+    // Keep the `IN (...)` list well under the bind parameter limits
+    for chunk in match_ids.chunks(1000) {
+        rows.extend(
+            Entity::find()
+                .filter(Column::MatchId.is_in(chunk.iter().copied()))
+                .order_by_asc(Column::Slot)
+                .find_also_related(players::Entity)
+                .all(db)
+                .await?,
+        );
+    }
+
+    let mut rosters: HashMap<i32, Rosters> = HashMap::new();
+    for (participant, player) in rows {
+        let Some(player) = player else {
+            continue;
+        };
+        let entry = rosters.entry(participant.match_id).or_default();
+        if participant.team == BLUE_TEAM {
+            entry.blue.push(player);
+        } else {
+            entry.red.push(player);
+        }
+    }
+    Ok(rosters)
+}
+
+/// Finds every match both players took part in, on either team.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn shared_matches<C: ConnectionTrait>(
+    db: &C,
+    first_player_id: i32,
+    second_player_id: i32,
+) -> ModelResult<Vec<SharedMatch>> {
+    let rows = Entity::find()
+        .filter(Column::PlayerId.is_in([first_player_id, second_player_id]))
+        .order_by_asc(Column::MatchId)
+        .all(db)
+        .await?;
+
+    let mut teams: HashMap<i32, (Option<String>, Option<String>)> = HashMap::new();
+    for row in rows {
+        let entry = teams.entry(row.match_id).or_default();
+        if row.player_id == first_player_id {
+            entry.0 = Some(row.team);
+        } else {
+            entry.1 = Some(row.team);
+        }
+    }
+
+    let mut shared = teams
+        .into_iter()
+        .filter_map(|(match_id, teams)| match teams {
+            (Some(first_team), Some(second_team)) => Some(SharedMatch {
+                match_id,
+                first_team,
+                second_team,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    shared.sort_by_key(|m| m.match_id);
+    Ok(shared)
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, QueryOrder};
+use serde::Serialize;
+
+pub use super::_entities::match_player_stats::{ActiveModel, Column, Entity, Model};
+use super::_entities::{matches, players};
+use crate::stats_log::{ParsedLog, PlayerLine};
+
+/// `source` of rows imported from a log uploaded by hand or read from disk.
+pub const UPLOADED: &str = "upload";
+/// `source` of rows imported from a download of the match's `stats_url`.
+pub const DOWNLOADED: &str = "download";
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// What importing a log into a match did.
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportSummary {
+    pub match_id: i32,
+    pub log_map: Option<String>,
+    /// The log's map differs from the match's, which usually means the log
+    /// belongs to another match.
+    pub map_mismatch: bool,
+    pub imported: usize,
+    /// Names in the log without a Steam ID linked to a player.
+    pub unmatched: Vec<String>,
+}
+
+/// A player's stats in one match, with the match they were recorded in.
+#[derive(Clone, Debug, Serialize)]
+pub struct PlayerMatchStats {
+    #[serde(flatten)]
+    pub stats: Model,
+    /// The bot's match number, `matches.match_id`.
+    pub match_number: Option<i32>,
+    pub map: Option<String>,
+    pub played_at: DateTimeUtc,
+}
+
+/// Replaces the per-player stats of `match_item` with the totals of `log`,
+/// recording `source` ([`UPLOADED`] or [`DOWNLOADED`]) on every row.
+/// Log players are mapped to players by Steam ID; the ones without a linked
+/// Steam ID are skipped and listed in the summary.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn import<C: ConnectionTrait>(
+    db: &C,
+    match_item: &matches::Model,
+    log: &ParsedLog,
+    source: &str,
+) -> ModelResult<ImportSummary> {
+    let mut totals: BTreeMap<i32, PlayerLine> = BTreeMap::new();
+    let mut unmatched = Vec::new();
+    for line in &log.players {
+        let player = match line.steam_id {
+            Some(steam_id) => players::Model::find_by_steam_id(db, steam_id).await?,
+            None => None,
+        };
+        let Some(player) = player else {
+            unmatched.push(line.name.clone());
+            continue;
+        };
+        // One person can show up twice after reconnecting with another name
+        let total = totals.entry(player.id).or_default();
+        total.team.clone_from(&line.team);
+        total.kills += line.kills;
+        total.deaths += line.deaths;
+        total.suicides += line.suicides;
+        total.teamkills += line.teamkills;
+        total.flag_caps += line.flag_caps;
+        total.flag_touches += line.flag_touches;
+    }
+
+    Entity::delete_many()
+        .filter(Column::MatchId.eq(match_item.id))
+        .exec(db)
+        .await?;
+    let rows = totals
+        .iter()
+        .map(|(&player_id, total)| ActiveModel {
+            match_id: ActiveValue::set(match_item.id),
+            player_id: ActiveValue::set(player_id),
+            team: ActiveValue::set(total.team.as_deref().map(str::to_lowercase)),
+            kills: ActiveValue::set(total.kills),
+            deaths: ActiveValue::set(total.deaths),
+            suicides: ActiveValue::set(total.suicides),
+            teamkills: ActiveValue::set(total.teamkills),
+            flag_caps: ActiveValue::set(total.flag_caps),
+            flag_touches: ActiveValue::set(total.flag_touches),
+            source: ActiveValue::set(source.to_string()),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    if !rows.is_empty() {
+        Entity::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(ImportSummary {
+        match_id: match_item.id,
+        map_mismatch: match (&log.map, &match_item.map) {
+            (Some(log_map), Some(map)) => !log_map.eq_ignore_ascii_case(map),
+            _ => false,
+        },
+        log_map: log.map.clone(),
+        imported: totals.len(),
+        unmatched,
+    })
+}
+
+/// Whether the stats of `match_id` (`matches.id`) came from an uploaded log.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn has_upload<C: ConnectionTrait>(db: &C, match_id: i32) -> ModelResult<bool> {
+    let count = Entity::find()
+        .filter(Column::MatchId.eq(match_id))
+        .filter(Column::Source.eq(UPLOADED))
+        .count(db)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Stats of `player_id` in every non-deleted match, newest first.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn for_player<C: ConnectionTrait>(
+    db: &C,
+    player_id: i32,
+) -> ModelResult<Vec<PlayerMatchStats>> {
+    Ok(Entity::find()
+        .filter(Column::PlayerId.eq(player_id))
+        .find_also_related(matches::Entity)
+        .filter(matches::Column::DeletedAt.is_null())
+        .order_by_desc(matches::Column::CreatedAt)
+        .order_by_desc(matches::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(stats, item)| {
+            let item = item?;
+            Some(PlayerMatchStats {
+                stats,
+                match_number: item.match_id,
+                map: item.map,
+                played_at: item.created_at,
+            })
+        })
+        .collect())
+}
+
+/// Stats of every player in `match_id` (`matches.id`).
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn for_match<C: ConnectionTrait>(db: &C, match_id: i32) -> ModelResult<Vec<Model>> {
+    let rows = Entity::find()
+        .filter(Column::MatchId.eq(match_id))
+        .order_by_asc(Column::Team)
+        .order_by_desc(Column::FlagCaps)
+        .order_by_desc(Column::Kills)
+        .all(db)
+        .await?;
+    Ok(rows)
+}
@@ -1,6 +1,441 @@
-use sea_orm::entity::prelude::*;
-use super::_entities::matches::ActiveModel;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use loco_rs::model::ModelResult;
+use sea_orm::{
+    entity::prelude::*, Condition, Order, PaginatorTrait, QueryOrder, QuerySelect, QueryTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::match_participants;
+use super::_entities::matches::{ActiveModel, Column, Entity, Model};
+use super::_entities::player_aliases;
+use super::match_participants::winning_outcome;
+use super::players;
+use super::seasons;
+use crate::elo;
 
 impl ActiveModelBehavior for ActiveModel {
     // extend activemodel below (keep comment for generators)
 }
+
+/// How a match ended for one team.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchResult {
+    Win,
+    Loss,
+    Draw,
+    /// The match has no reported outcome (`match_outcome` null or unknown).
+    Undecided,
+}
+
+impl Model {
+    /// Result of this match for `team` (`"blue"` or `"red"`). An outcome of
+    /// 0 is a draw, 1 a blue win and 2 a red win.
+    pub fn result_for(&self, team: &str) -> MatchResult {
+        match self.match_outcome {
+            Some(0) => MatchResult::Draw,
+            Some(outcome) if outcome == winning_outcome(team) => MatchResult::Win,
+            Some(1 | 2) => MatchResult::Loss,
+            _ => MatchResult::Undecided,
+        }
+    }
+
+    /// Blue and red scores, or `None` while the match is undecided or a
+    /// score is missing.
+    pub fn score_line(&self) -> Option<ScoreLine> {
+        let (winning, losing) = (self.winning_score?, self.losing_score?);
+        match self.match_outcome? {
+            2 => Some(ScoreLine {
+                blue: losing,
+                red: winning,
+            }),
+            0 | 1 => Some(ScoreLine {
+                blue: winning,
+                red: losing,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ScoreLine {
+    pub blue: i32,
+    pub red: i32,
+}
+
+/// Win/loss/draw tally. Undecided matches are counted but left out of
+/// `games_played` and `winrate`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Record {
+    pub games_played: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub undecided: usize,
+    pub winrate: f64,
+}
+
+impl Record {
+    /// A record of decided matches only, e.g. read back from stored counts.
+    pub fn from_counts(wins: usize, losses: usize, draws: usize) -> Self {
+        let mut record = Self {
+            wins,
+            losses,
+            draws,
+            ..Default::default()
+        };
+        record.update_totals();
+        record
+    }
+
+    pub fn add(&mut self, result: MatchResult) {
+        match result {
+            MatchResult::Win => self.wins += 1,
+            MatchResult::Loss => self.losses += 1,
+            MatchResult::Draw => self.draws += 1,
+            MatchResult::Undecided => self.undecided += 1,
+        }
+        self.update_totals();
+    }
+
+    fn update_totals(&mut self) {
+        self.games_played = self.wins + self.losses + self.draws;
+        self.winrate = if self.games_played > 0 {
+            (self.wins as f64) / (self.games_played as f64)
+        } else {
+            0.0
+        };
+    }
+}
+
+/// How a set of matches ended, counted per side.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OutcomeSplit {
+    pub times_played: usize,
+    pub blue_wins: usize,
+    pub red_wins: usize,
+    pub draws: usize,
+    /// Matches without a reported outcome.
+    pub undecided: usize,
+    /// Share of decided matches won by blue.
+    pub blue_winrate: f64,
+    /// Share of decided matches that were draws.
+    pub draw_rate: f64,
+    pub average_winning_score: Option<f64>,
+    pub average_losing_score: Option<f64>,
+    #[serde(skip)]
+    scored: usize,
+    #[serde(skip)]
+    winning_total: i64,
+    #[serde(skip)]
+    losing_total: i64,
+}
+
+impl OutcomeSplit {
+    pub fn add(&mut self, item: &Model) {
+        self.times_played += 1;
+        match item.match_outcome {
+            Some(0) => self.draws += 1,
+            Some(1) => self.blue_wins += 1,
+            Some(2) => self.red_wins += 1,
+            _ => self.undecided += 1,
+        }
+        if let (Some(0..=2), Some(winning), Some(losing)) =
+            (item.match_outcome, item.winning_score, item.losing_score)
+        {
+            self.scored += 1;
+            self.winning_total += i64::from(winning);
+            self.losing_total += i64::from(losing);
+        }
+
+        let decided = self.blue_wins + self.red_wins + self.draws;
+        if decided > 0 {
+            self.blue_winrate = self.blue_wins as f64 / decided as f64;
+            self.draw_rate = self.draws as f64 / decided as f64;
+        }
+        if self.scored > 0 {
+            self.average_winning_score = Some(self.winning_total as f64 / self.scored as f64);
+            self.average_losing_score = Some(self.losing_total as f64 / self.scored as f64);
+        }
+    }
+}
+
+/// Probabilities are clamped this far from 0 and 1 so a certain prediction
+/// that went wrong has a finite log-loss.
+const LOG_LOSS_EPSILON: f64 = 1e-6;
+
+/// How well `blue_probability` predicted a set of decided matches.
+///
+/// `accuracy` is the share of matches with a favourite that the favourite
+/// won; draws and even odds have no favourite. `brier_score` is the mean
+/// squared error of `blue_probability` against blue's score (1, 0.5 or 0),
+/// and `log_loss` the mean cross-entropy against the same score, so lower
+/// is better for both.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PredictionRecord {
+    /// Decided matches that had a `blue_probability`.
+    pub rated: usize,
+    pub predicted: usize,
+    pub correct: usize,
+    pub accuracy: Option<f64>,
+    pub brier_score: Option<f64>,
+    pub log_loss: Option<f64>,
+    #[serde(skip)]
+    squared_error: f64,
+    #[serde(skip)]
+    cross_entropy: f64,
+}
+
+impl PredictionRecord {
+    pub fn add(&mut self, item: &Model) {
+        let Some(probability) = item.blue_probability.map(f64::from) else {
+            return;
+        };
+        let Some(score) = elo::blue_score(item.match_outcome) else {
+            return;
+        };
+
+        self.rated += 1;
+        self.squared_error += (probability - score).powi(2);
+        self.brier_score = Some(self.squared_error / self.rated as f64);
+        let clamped = probability.clamp(LOG_LOSS_EPSILON, 1.0 - LOG_LOSS_EPSILON);
+        self.cross_entropy -= score * clamped.ln() + (1.0 - score) * (1.0 - clamped).ln();
+        self.log_loss = Some(self.cross_entropy / self.rated as f64);
+
+        let favourite_blue = match probability.partial_cmp(&0.5) {
+            Some(std::cmp::Ordering::Greater) => Some(true),
+            Some(std::cmp::Ordering::Less) => Some(false),
+            _ => None,
+        };
+        if let (Some(favourite_blue), Some(1 | 2)) = (favourite_blue, item.match_outcome) {
+            self.predicted += 1;
+            if favourite_blue == (item.match_outcome == Some(1)) {
+                self.correct += 1;
+            }
+            self.accuracy = Some(self.correct as f64 / self.predicted as f64);
+        }
+    }
+}
+
+/// Optional `from`/`to` query params limiting matches by `created_at`. Both
+/// are UTC calendar days and both are inclusive.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn condition(&self) -> Condition {
+        self.condition_on(Column::CreatedAt)
+    }
+
+    /// The same range applied to another timestamp column.
+    pub fn condition_on<C: ColumnTrait>(&self, column: C) -> Condition {
+        let mut condition = Condition::all();
+        if let Some(from) = self.from {
+            condition = condition.add(column.gte(from.and_time(NaiveTime::MIN).and_utc()));
+        }
+        if let Some(to) = self.to {
+            let end = (to + Duration::days(1)).and_time(NaiveTime::MIN).and_utc();
+            condition = condition.add(column.lt(end));
+        }
+        condition
+    }
+}
+
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+pub const MAX_PAGE_SIZE: u64 = 200;
+
+/// Outcome filter for match listings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutcomeFilter {
+    Draw,
+    Blue,
+    Red,
+    /// No outcome reported yet.
+    Undecided,
+}
+
+/// Column a match listing is sorted by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSort {
+    #[default]
+    CreatedAt,
+    MatchId,
+    Map,
+    Server,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Query params of the match listings: filters, sorting and pagination.
+///
+/// `cursor` is the number of matches to skip, as returned in
+/// [`Page::next_cursor`]; `limit` defaults to [`DEFAULT_PAGE_SIZE`] and is
+/// capped at [`MAX_PAGE_SIZE`].
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ListParams {
+    pub map: Option<String>,
+    pub server: Option<String>,
+    pub game_type: Option<String>,
+    pub outcome: Option<OutcomeFilter>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Start of a player name, ignoring case; only matches a player whose
+    /// name or former name starts with it took part in are listed.
+    pub player: Option<String>,
+    /// Season name; only matches played during the season are listed.
+    pub season: Option<String>,
+    #[serde(default)]
+    pub sort: MatchSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u64>,
+    pub cursor: Option<u64>,
+}
+
+/// One page of a listing, with the total number of matching rows.
+#[derive(Clone, Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub limit: u64,
+    /// Cursor of the next page, `None` on the last one.
+    pub next_cursor: Option<u64>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+impl ListParams {
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::all()
+            .add(Column::DeletedAt.is_null())
+            .add(
+                DateRange {
+                    from: self.from,
+                    to: self.to,
+                }
+                .condition(),
+            );
+        if let Some(map) = &self.map {
+            condition = condition.add(Column::Map.eq(map.as_str()));
+        }
+        if let Some(server) = &self.server {
+            condition = condition.add(Column::Server.eq(server.as_str()));
+        }
+        if let Some(game_type) = &self.game_type {
+            condition = condition.add(Column::GameType.eq(game_type.as_str()));
+        }
+        if let Some(outcome) = self.outcome {
+            condition = condition.add(match outcome {
+                OutcomeFilter::Draw => Column::MatchOutcome.eq(0),
+                OutcomeFilter::Blue => Column::MatchOutcome.eq(1),
+                OutcomeFilter::Red => Column::MatchOutcome.eq(2),
+                OutcomeFilter::Undecided => Column::MatchOutcome.is_null(),
+            });
+        }
+        condition
+    }
+
+    /// Loads the page of non-deleted matches selected by these params.
+    /// An unknown `season` yields an empty page.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn fetch<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Page<Model>> {
+        let limit = self.limit();
+        let cursor = self.cursor.unwrap_or_default();
+
+        let empty = Page {
+            items: vec![],
+            total: 0,
+            limit,
+            next_cursor: None,
+        };
+
+        let mut query = Entity::find().filter(self.condition());
+        if let Some(name) = &self.season {
+            let Some(season) = seasons::Model::find_by_name(db, name).await? else {
+                return Ok(empty);
+            };
+            query = query.filter(season.match_condition());
+        }
+        if let Some(prefix) = self.player.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            let by_name = players::Entity::find()
+                .select_only()
+                .column(players::Column::Id)
+                .filter(players::name_starts_with(players::Column::PlayerName, prefix))
+                .into_query();
+            let by_alias = player_aliases::Entity::find()
+                .select_only()
+                .column(player_aliases::Column::PlayerId)
+                .filter(players::name_starts_with(player_aliases::Column::Alias, prefix))
+                .into_query();
+            query = query.filter(
+                Column::Id.in_subquery(
+                    match_participants::Entity::find()
+                        .select_only()
+                        .column(match_participants::Column::MatchId)
+                        .filter(
+                            Condition::any()
+                                .add(match_participants::Column::PlayerId.in_subquery(by_name))
+                                .add(match_participants::Column::PlayerId.in_subquery(by_alias)),
+                        )
+                        .into_query(),
+                ),
+            );
+        }
+
+        let total = query.clone().count(db).await?;
+
+        let order = match self.order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        };
+        let column = match self.sort {
+            MatchSort::CreatedAt => Column::CreatedAt,
+            MatchSort::MatchId => Column::MatchId,
+            MatchSort::Map => Column::Map,
+            MatchSort::Server => Column::Server,
+        };
+        // Break ties on the primary key so pages never overlap
+        let items = query
+            .order_by(column, order.clone())
+            .order_by(Column::Id, order)
+            .offset(cursor)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let next = cursor + limit;
+        Ok(Page {
+            items,
+            total,
+            limit,
+            next_cursor: (next < total).then_some(next),
+        })
+    }
+}
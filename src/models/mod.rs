@@ -1,5 +1,22 @@
 pub mod _entities;
+pub mod achievements;
+pub mod calibration;
+pub mod leaderboard;
+pub mod map_stats;
 pub mod notes;
+pub mod player_achievements;
+pub mod player_aliases;
+pub mod player_search;
+pub mod match_participants;
+pub mod match_player_stats;
 pub mod matches;
 pub mod players;
 pub mod player_elo;
+pub mod player_penalties;
+pub mod player_stats;
+pub mod season_standings;
+pub mod seasons;
+pub mod stats_snapshots;
+pub mod steam_report;
+pub mod server_stats;
+pub mod users;
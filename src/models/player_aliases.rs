@@ -0,0 +1,162 @@
+//! Names players went by before, so lookups by an old name still find them
+//! after a rename in Discord.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel, QueryOrder};
+
+pub use super::_entities::player_aliases::{ActiveModel, Column, Entity, Model};
+use super::_entities::{player_elo, players};
+use super::players::name_eq;
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// Records that `player_id` went by `alias` until `last_used_at`. Names
+/// differing only in case are the same alias.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    player_id: i32,
+    alias: &str,
+    last_used_at: DateTimeUtc,
+) -> Result<(), DbErr> {
+    let alias = alias.trim();
+    if alias.is_empty() {
+        return Ok(());
+    }
+    let existing = Entity::find()
+        .filter(Column::PlayerId.eq(player_id))
+        .filter(name_eq(Column::Alias, alias))
+        .one(db)
+        .await?;
+    match existing {
+        Some(existing) if existing.last_used_at >= last_used_at => {}
+        Some(existing) => {
+            let mut item = existing.into_active_model();
+            item.last_used_at = ActiveValue::set(last_used_at);
+            item.update(db).await?;
+        }
+        None => {
+            ActiveModel {
+                player_id: ActiveValue::set(player_id),
+                alias: ActiveValue::set(alias.to_string()),
+                last_used_at: ActiveValue::set(last_used_at),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Records the name a player is renamed away from, and drops the alias of
+/// the name they are renamed to, which is current again.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn rename<C: ConnectionTrait>(
+    db: &C,
+    player_id: i32,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), DbErr> {
+    record(db, player_id, old_name, Utc::now()).await?;
+    Entity::delete_many()
+        .filter(Column::PlayerId.eq(player_id))
+        .filter(name_eq(Column::Alias, new_name.trim()))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// The player who most recently went by `alias`, ignoring case.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn find_player_id<C: ConnectionTrait>(db: &C, alias: &str) -> ModelResult<Option<i32>> {
+    Ok(Entity::find()
+        .filter(name_eq(Column::Alias, alias.trim()))
+        .order_by_desc(Column::LastUsedAt)
+        .one(db)
+        .await?
+        .map(|alias| alias.player_id))
+}
+
+/// Former names of `player_id`, most recently used first.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn for_player<C: ConnectionTrait>(db: &C, player_id: i32) -> ModelResult<Vec<Model>> {
+    Ok(Entity::find()
+        .filter(Column::PlayerId.eq(player_id))
+        .order_by_desc(Column::LastUsedAt)
+        .all(db)
+        .await?)
+}
+
+/// Records as aliases the names in each player's Elo history that differ
+/// from their current name, for renames made before aliases were kept.
+/// Entries are matched to players by Discord ID. Returns the number of
+/// names found.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn backfill_from_elo_history<C: ConnectionTrait>(db: &C) -> ModelResult<usize> {
+    let players = players::Entity::find()
+        .filter(players::Column::DiscordId.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|player| {
+            let discord_id = player.discord_id.as_deref()?.trim().parse::<i64>().ok()?;
+            Some((discord_id, player))
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Latest use of every (player, lowercased name)
+    let mut names: HashMap<(i32, String), (String, DateTimeUtc)> = HashMap::new();
+    for entry in player_elo::Entity::find()
+        .filter(player_elo::Column::DiscordId.is_in(players.keys().copied()))
+        .all(db)
+        .await?
+    {
+        let (Some(player), Some(name)) = (
+            entry.discord_id.and_then(|id| players.get(&id)),
+            entry.player_name.as_deref().map(str::trim),
+        ) else {
+            continue;
+        };
+        if name.is_empty()
+            || player
+                .player_name
+                .as_deref()
+                .is_some_and(|current| current.trim().eq_ignore_ascii_case(name))
+        {
+            continue;
+        }
+        let used_at = entry.created_at.unwrap_or(player.updated_at);
+        let latest = names
+            .entry((player.id, name.to_lowercase()))
+            .or_insert_with(|| (name.to_string(), used_at));
+        if used_at > latest.1 {
+            *latest = (name.to_string(), used_at);
+        }
+    }
+
+    for ((player_id, _), (name, used_at)) in &names {
+        record(db, *player_id, name, *used_at).await?;
+    }
+    Ok(names.len())
+}
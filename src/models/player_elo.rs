@@ -1,6 +1,255 @@
-use sea_orm::entity::prelude::*;
-use super::_entities::player_elo::ActiveModel;
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveTime};
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, Condition, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::player_elo::{ActiveModel, Column, Entity, Model};
+use super::_entities::players;
+use super::players::name_eq;
 
 impl ActiveModelBehavior for ActiveModel {
     // extend activemodel below (keep comment for generators)
 }
+
+impl Model {
+    /// Elo history of a player, oldest entry first. The name is matched
+    /// ignoring case.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn history_for_name<C: ConnectionTrait>(
+        db: &C,
+        player_name: &str,
+    ) -> ModelResult<Vec<Self>> {
+        let history = Entity::find()
+            .filter(name_eq(Column::PlayerName, player_name))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(history)
+    }
+
+    /// Elo history of a player, oldest entry first. Entries are matched by
+    /// Discord ID, falling back to the player name when the player has no
+    /// numeric Discord ID.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn history_for_player<C: ConnectionTrait>(
+        db: &C,
+        player: &players::Model,
+    ) -> ModelResult<Vec<Self>> {
+        Self::history_for_player_matching(db, player, Condition::all()).await
+    }
+
+    /// [`Self::history_for_player`] limited to the entries selected by
+    /// `condition`.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn history_for_player_matching<C: ConnectionTrait>(
+        db: &C,
+        player: &players::Model,
+        condition: Condition,
+    ) -> ModelResult<Vec<Self>> {
+        let discord_id = player
+            .discord_id
+            .as_deref()
+            .and_then(|id| id.trim().parse::<i64>().ok());
+        let query = match (discord_id, player.player_name.as_deref()) {
+            (Some(discord_id), _) => Entity::find().filter(Column::DiscordId.eq(discord_id)),
+            (None, Some(name)) => Entity::find().filter(name_eq(Column::PlayerName, name)),
+            (None, None) => return Ok(vec![]),
+        };
+        let history = query
+            .filter(condition)
+            .order_by_asc(Column::CreatedAt)
+            .order_by_asc(Column::EntryId)
+            .all(db)
+            .await?;
+        Ok(history)
+    }
+
+    /// The entry recorded before this one for the same player, i.e. the
+    /// rating they went into this entry's match with.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn previous<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Option<Self>> {
+        let Some(discord_id) = self.discord_id else {
+            return Ok(None);
+        };
+        // Same ordering as `history_for_player`: by time, then by entry id
+        let earlier = match self.created_at {
+            Some(created_at) => Condition::any()
+                .add(Column::CreatedAt.lt(created_at))
+                .add(
+                    Condition::all()
+                        .add(Column::CreatedAt.eq(created_at))
+                        .add(Column::EntryId.lt(self.entry_id)),
+                ),
+            None => Condition::all().add(Column::EntryId.lt(self.entry_id)),
+        };
+        let previous = Entity::find()
+            .filter(Column::DiscordId.eq(discord_id))
+            .filter(earlier)
+            .order_by_desc(Column::CreatedAt)
+            .order_by_desc(Column::EntryId)
+            .one(db)
+            .await?;
+        Ok(previous)
+    }
+}
+
+/// Ratings recorded for the given matches, keyed by `(match_id, discord_id)`
+/// where `match_id` is the bot's match number (`matches.match_id`). Each
+/// value is the rating the player ended that match with.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn ratings_for_matches<C: ConnectionTrait>(
+    db: &C,
+    match_ids: Vec<i64>,
+) -> ModelResult<HashMap<(i64, i64), i32>> {
+    let mut ratings = HashMap::new();
+    for chunk in match_ids.chunks(1000) {
+        let entries = Entity::find()
+            .filter(Column::MatchId.is_in(chunk.iter().copied()))
+            .order_by_asc(Column::EntryId)
+            .all(db)
+            .await?;
+        ratings.extend(
+            entries
+                .into_iter()
+                .filter_map(|e| Some(((e.match_id?, e.discord_id?), e.player_elos?))),
+        );
+    }
+    Ok(ratings)
+}
+
+/// Every player's latest rating recorded before `at`, keyed by Discord ID.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn ratings_at<C: ConnectionTrait>(
+    db: &C,
+    at: DateTimeUtc,
+) -> ModelResult<HashMap<i64, i32>> {
+    let entries = Entity::find()
+        .filter(Column::CreatedAt.lt(at))
+        .order_by_asc(Column::CreatedAt)
+        .order_by_asc(Column::EntryId)
+        .all(db)
+        .await?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|e| Some((e.discord_id?, e.player_elos?)))
+        .collect())
+}
+
+/// Rating change each entry records, keyed by `player_elo.match_id` (the
+/// bot's match number, `matches.match_id`). The change is measured against
+/// the previous rated entry, and the first one against `starting_rating`.
+pub fn changes_by_match(history: &[Model], starting_rating: i32) -> HashMap<i64, i32> {
+    let mut previous = starting_rating;
+    let mut changes = HashMap::new();
+    for entry in history {
+        let Some(rating) = entry.player_elos else {
+            continue;
+        };
+        if let Some(match_id) = entry.match_id {
+            changes.insert(match_id, rating - previous);
+        }
+        previous = rating;
+    }
+    changes
+}
+
+/// Time span that [`bucketed`] groups entries by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    /// One point per entry.
+    #[default]
+    Match,
+    /// UTC calendar days.
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+}
+
+/// Ratings within one bucket: the first, highest, lowest and last one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SeriesPoint {
+    pub start: DateTimeUtc,
+    /// Exclusive end of the bucket; equal to `start` for per-match points.
+    pub end: DateTimeUtc,
+    /// The match of a per-match point.
+    pub match_id: Option<i64>,
+    pub entries: usize,
+    pub open: i32,
+    pub high: i32,
+    pub low: i32,
+    pub close: i32,
+}
+
+impl Bucket {
+    fn span(self, at: DateTimeUtc) -> (DateTimeUtc, DateTimeUtc) {
+        let day = at.date_naive();
+        match self {
+            Self::Match => (at, at),
+            Self::Day => {
+                let start = day.and_time(NaiveTime::MIN).and_utc();
+                (start, start + Duration::days(1))
+            }
+            Self::Week => {
+                let monday = day - Duration::days(i64::from(day.weekday().num_days_from_monday()));
+                let start = monday.and_time(NaiveTime::MIN).and_utc();
+                (start, start + Duration::days(7))
+            }
+        }
+    }
+}
+
+/// Groups a history, oldest entry first, into buckets. Entries without a
+/// timestamp or rating are left out.
+pub fn bucketed(history: &[Model], bucket: Bucket) -> Vec<SeriesPoint> {
+    let mut points: Vec<SeriesPoint> = Vec::new();
+    for entry in history {
+        let (Some(at), Some(rating)) = (entry.created_at, entry.player_elos) else {
+            continue;
+        };
+        let (start, end) = bucket.span(at);
+        match points.last_mut() {
+            Some(point) if bucket != Bucket::Match && point.start == start => {
+                point.entries += 1;
+                point.high = point.high.max(rating);
+                point.low = point.low.min(rating);
+                point.close = rating;
+            }
+            _ => points.push(SeriesPoint {
+                start,
+                end,
+                match_id: if bucket == Bucket::Match {
+                    entry.match_id
+                } else {
+                    None
+                },
+                entries: 1,
+                open: rating,
+                high: rating,
+                low: rating,
+                close: rating,
+            }),
+        }
+    }
+    points
+}
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, sea_query::Expr, Condition, QueryOrder, QuerySelect};
+
+pub use super::_entities::player_penalties::{ActiveModel, Column, Entity, Model};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// Penalties in force at `at`: not revoked, not marked expired, and either
+/// open-ended or expiring after `at`.
+pub fn active_condition(at: DateTimeUtc) -> Condition {
+    Condition::all()
+        .add(Column::RevokedAt.is_null())
+        .add(Column::ExpiredAt.is_null())
+        .add(Column::IssuedAt.lte(at))
+        .add(
+            Condition::any()
+                .add(Column::ExpiresAt.is_null())
+                .add(Column::ExpiresAt.gt(at)),
+        )
+}
+
+impl Model {
+    /// Whether the penalty is in force at `at`, see [`active_condition`].
+    pub fn is_active(&self, at: DateTimeUtc) -> bool {
+        self.revoked_at.is_none()
+            && self.expired_at.is_none()
+            && self.issued_at <= at
+            && self.expires_at.is_none_or(|expires_at| expires_at > at)
+    }
+
+    /// Penalties in force now, newest first.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn active<C: ConnectionTrait>(db: &C) -> ModelResult<Vec<Self>> {
+        let penalties = Entity::find()
+            .filter(active_condition(Utc::now()))
+            .order_by_desc(Column::IssuedAt)
+            .order_by_desc(Column::Id)
+            .all(db)
+            .await?;
+        Ok(penalties)
+    }
+
+    /// Every penalty of `player_id`, newest first.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn history<C: ConnectionTrait>(db: &C, player_id: i32) -> ModelResult<Vec<Self>> {
+        let penalties = Entity::find()
+            .filter(Column::PlayerId.eq(player_id))
+            .order_by_desc(Column::IssuedAt)
+            .order_by_desc(Column::Id)
+            .all(db)
+            .await?;
+        Ok(penalties)
+    }
+}
+
+/// Ids of the players with a penalty in force now, i.e. currently dunced.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn dunced_players<C: ConnectionTrait>(db: &C) -> ModelResult<HashSet<i32>> {
+    let player_ids = Entity::find()
+        .select_only()
+        .column(Column::PlayerId)
+        .filter(active_condition(Utc::now()))
+        .into_tuple::<i32>()
+        .all(db)
+        .await?;
+    Ok(player_ids.into_iter().collect())
+}
+
+/// Whether `player_id` has a penalty in force now.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn is_dunced<C: ConnectionTrait>(db: &C, player_id: i32) -> ModelResult<bool> {
+    let active = Entity::find()
+        .filter(Column::PlayerId.eq(player_id))
+        .filter(active_condition(Utc::now()))
+        .count(db)
+        .await?;
+    Ok(active > 0)
+}
+
+/// Marks penalties whose `expires_at` has passed as expired, so the history
+/// tells them apart from revoked ones. Returns the number marked.
+///
+/// # Errors
+///
+/// When could not write to the database
+pub async fn expire<C: ConnectionTrait>(db: &C) -> ModelResult<u64> {
+    let now = Utc::now();
+    let result = Entity::update_many()
+        .col_expr(Column::ExpiredAt, Expr::col(Column::ExpiresAt).into())
+        .filter(Column::RevokedAt.is_null())
+        .filter(Column::ExpiredAt.is_null())
+        .filter(Column::ExpiresAt.lte(now))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,153 @@
+//! Player search by current and former names, for lookups where the exact
+//! spelling is not known.
+
+use std::collections::HashMap;
+
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, sea_query::Func, Condition, QueryOrder, QuerySelect, QueryTrait};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::{player_aliases, players};
+use super::players::name_starts_with;
+use crate::fuzzy;
+
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 50;
+/// Least [`fuzzy::similarity`] for a fuzzy match.
+pub const MIN_SIMILARITY: f64 = 0.4;
+/// Former names score a little below the current name they tie with.
+const ALIAS_WEIGHT: f64 = 0.95;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Fuzzy,
+    Prefix,
+    Exact,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub player: players::Model,
+    /// The name that matched, current or former.
+    pub matched_name: String,
+    pub alias: bool,
+    pub match_kind: MatchKind,
+    /// From 0 to 1; exact matches score 1, prefixes above every fuzzy match.
+    pub score: f64,
+}
+
+/// How well `name` matches the lowercased `query`.
+pub fn match_name(query: &str, name: &str) -> Option<(MatchKind, f64)> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    if name == query {
+        return Some((MatchKind::Exact, 1.0));
+    }
+    if name.starts_with(query) {
+        // Shorter names are closer to what was typed; stays within 0.5..1
+        let covered = query.chars().count() as f64 / name.chars().count() as f64;
+        return Some((MatchKind::Prefix, 0.5 + 0.49 * covered));
+    }
+    let similarity = fuzzy::similarity(query, &name);
+    (similarity >= MIN_SIMILARITY).then_some((MatchKind::Fuzzy, 0.5 * similarity))
+}
+
+/// Names in `column` that [`match_name`] could accept for the lowercased
+/// `query`: the ones starting with it, and the ones whose length allows a
+/// [`MIN_SIMILARITY`] fuzzy match. Both of [`fuzzy::similarity`]'s measures
+/// stay below the ratio of the shorter to the longer length (plus one for the
+/// trigram padding), so only names of one letter repeated over and over could
+/// match from outside that window.
+fn candidates<C: ColumnTrait>(column: C, query: &str) -> Condition {
+    let padded = query.chars().count() as f64 + 1.0;
+    let shortest = ((padded * MIN_SIMILARITY).ceil() - 1.0).max(0.0);
+    let longest = (padded / MIN_SIMILARITY).floor() - 1.0;
+    let length = Expr::expr(Func::char_length(Expr::col((column.entity_name(), column))));
+    Condition::any()
+        .add(name_starts_with(column, query))
+        .add(length.between(shortest as i64, longest as i64))
+}
+
+/// Non-deleted players whose current or former name matches `params.q`,
+/// best match first. Only the [`candidates`] are loaded and scored.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn search<C: ConnectionTrait>(
+    db: &C,
+    params: &SearchParams,
+) -> ModelResult<Vec<SearchHit>> {
+    let query = params.q.trim().to_lowercase();
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let by_alias = player_aliases::Entity::find()
+        .select_only()
+        .column(player_aliases::Column::PlayerId)
+        .filter(candidates(player_aliases::Column::Alias, &query))
+        .into_query();
+    let players = players::Entity::find()
+        .filter(players::Column::DeletedAt.is_null())
+        .filter(
+            Condition::any()
+                .add(candidates(players::Column::PlayerName, &query))
+                .add(players::Column::Id.in_subquery(by_alias)),
+        )
+        .order_by_asc(players::Column::Id)
+        .all(db)
+        .await?;
+    let mut aliases: HashMap<i32, Vec<String>> = HashMap::new();
+    for alias in player_aliases::Entity::find()
+        .filter(player_aliases::Column::PlayerId.is_in(players.iter().map(|p| p.id)))
+        .filter(candidates(player_aliases::Column::Alias, &query))
+        .all(db)
+        .await?
+    {
+        aliases.entry(alias.player_id).or_default().push(alias.alias);
+    }
+
+    let mut hits = players
+        .into_iter()
+        .filter_map(|player| {
+            let current = player
+                .player_name
+                .iter()
+                .filter_map(|name| Some((name, false, match_name(&query, name)?)));
+            let former = aliases.get(&player.id).into_iter().flatten().filter_map(|name| {
+                let (kind, score) = match_name(&query, name)?;
+                Some((name, true, (kind, score * ALIAS_WEIGHT)))
+            });
+            let (name, alias, (match_kind, score)) = current
+                .chain(former)
+                .max_by(|a, b| a.2 .1.total_cmp(&b.2 .1))?;
+            Some(SearchHit {
+                matched_name: name.clone(),
+                alias,
+                match_kind,
+                score,
+                player,
+            })
+        })
+        .collect::<Vec<_>>();
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.player.current_elo.cmp(&a.player.current_elo))
+            .then_with(|| a.player.id.cmp(&b.player.id))
+    });
+    hits.truncate(limit);
+    Ok(hits)
+}
@@ -0,0 +1,207 @@
+//! Player statistics computed live from the `matches` history, as opposed
+//! to the counters the bot keeps on the `players` row.
+
+use std::collections::BTreeMap;
+
+use chrono::Datelike;
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, QueryOrder};
+use serde::Serialize;
+
+use super::_entities::{match_participants, matches, player_elo, players};
+use super::match_participants::BLUE_TEAM;
+use super::matches::{MatchResult, Record};
+use super::seasons;
+
+/// `matches.game_type` of deathmatch games, which the bot counts in
+/// `dm_wins`/`dm_losses`. Every other game type counts as a pug.
+pub const DM_GAME_TYPE: &str = "dm";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreakKind {
+    Win,
+    Loss,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Streak {
+    pub kind: StreakKind,
+    pub length: usize,
+}
+
+/// Win and loss streaks over decided matches. A draw ends any streak;
+/// undecided matches are skipped.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Streaks {
+    pub current: Option<Streak>,
+    pub longest_win: usize,
+    pub longest_loss: usize,
+}
+
+impl Streaks {
+    pub fn add(&mut self, result: MatchResult) {
+        let kind = match result {
+            MatchResult::Win => StreakKind::Win,
+            MatchResult::Loss => StreakKind::Loss,
+            MatchResult::Draw => {
+                self.current = None;
+                return;
+            }
+            MatchResult::Undecided => return,
+        };
+        let length = match self.current {
+            Some(streak) if streak.kind == kind => streak.length + 1,
+            _ => 1,
+        };
+        self.current = Some(Streak { kind, length });
+        match kind {
+            StreakKind::Win => self.longest_win = self.longest_win.max(length),
+            StreakKind::Loss => self.longest_loss = self.longest_loss.max(length),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TeamRecords {
+    pub blue: Record,
+    pub red: Record,
+}
+
+/// Number of matches played in one ISO week, e.g. `2024-W40`.
+#[derive(Clone, Debug, Serialize)]
+pub struct WeekCount {
+    pub week: String,
+    pub games: usize,
+}
+
+/// A stored `players` counter that disagrees with the match history.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CounterMismatch {
+    pub counter: &'static str,
+    pub stored: Option<i32>,
+    pub computed: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PlayerStats {
+    pub overall: Record,
+    pub by_game_type: BTreeMap<String, Record>,
+    pub by_map: BTreeMap<String, Record>,
+    pub by_team: TeamRecords,
+    pub streaks: Streaks,
+    pub peak_elo: Option<i32>,
+    pub lowest_elo: Option<i32>,
+    pub games_per_week: Vec<WeekCount>,
+    pub counter_mismatches: Vec<CounterMismatch>,
+}
+
+impl PlayerStats {
+    /// Computes the statistics of `player` from their non-deleted matches and
+    /// Elo history, limited to one season when `season` is given.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn compute<C: ConnectionTrait>(
+        db: &C,
+        player: &players::Model,
+        season: Option<&seasons::Model>,
+    ) -> ModelResult<Self> {
+        let mut query = match_participants::Entity::find()
+            .filter(match_participants::Column::PlayerId.eq(player.id))
+            .find_also_related(matches::Entity)
+            .filter(matches::Column::DeletedAt.is_null());
+        if let Some(season) = season {
+            query = query.filter(season.match_condition());
+        }
+        let played = query
+            .order_by_asc(matches::Column::CreatedAt)
+            .order_by_asc(matches::Column::Id)
+            .all(db)
+            .await?;
+
+        let mut stats = Self::default();
+        let mut pugs = Record::default();
+        let mut dms = Record::default();
+        let mut weeks: BTreeMap<(i32, u32), usize> = BTreeMap::new();
+        for (participant, match_item) in played {
+            let Some(match_item) = match_item else {
+                continue;
+            };
+            let result = match_item.result_for(&participant.team);
+            let game_type = match_item
+                .game_type
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+
+            stats.overall.add(result);
+            if game_type.eq_ignore_ascii_case(DM_GAME_TYPE) {
+                dms.add(result);
+            } else {
+                pugs.add(result);
+            }
+            stats.by_game_type.entry(game_type).or_default().add(result);
+            stats
+                .by_map
+                .entry(match_item.map.clone().unwrap_or_else(|| "unknown".to_string()))
+                .or_default()
+                .add(result);
+            if participant.team == BLUE_TEAM {
+                stats.by_team.blue.add(result);
+            } else {
+                stats.by_team.red.add(result);
+            }
+            stats.streaks.add(result);
+
+            let week = match_item.created_at.iso_week();
+            *weeks.entry((week.year(), week.week())).or_default() += 1;
+        }
+
+        stats.games_per_week = weeks
+            .into_iter()
+            .map(|((year, week), games)| WeekCount {
+                week: format!("{year}-W{week:02}"),
+                games,
+            })
+            .collect();
+
+        let ratings = player_elo::Model::history_for_player(db, player)
+            .await?
+            .into_iter()
+            .filter(|e| match (season, e.created_at) {
+                (Some(season), Some(created_at)) => season.contains(created_at),
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .filter_map(|e| e.player_elos)
+            .collect::<Vec<_>>();
+        stats.peak_elo = ratings.iter().copied().max();
+        stats.lowest_elo = ratings.iter().copied().min();
+
+        // The stored counters are all-time, so a season can't be checked
+        if season.is_some() {
+            return Ok(stats);
+        }
+        let counters = [
+            ("pug_wins", player.pug_wins, pugs.wins),
+            ("pug_losses", player.pug_losses, pugs.losses),
+            ("pug_draws", player.pug_draws, pugs.draws),
+            ("dm_wins", player.dm_wins, dms.wins),
+            ("dm_losses", player.dm_losses, dms.losses),
+        ];
+        stats.counter_mismatches = counters
+            .into_iter()
+            .filter(|&(_, stored, computed)| {
+                usize::try_from(stored.unwrap_or_default()).ok() != Some(computed)
+            })
+            .map(|(counter, stored, computed)| CounterMismatch {
+                counter,
+                stored,
+                computed,
+            })
+            .collect();
+
+        Ok(stats)
+    }
+}
@@ -1,6 +1,164 @@
+use async_trait::async_trait;
+use loco_rs::model::ModelResult;
 use sea_orm::entity::prelude::*;
-use super::_entities::players::ActiveModel;
+use sea_orm::sea_query::{Func, LikeExpr, SimpleExpr};
+use sea_orm::{ActiveValue, QueryOrder};
 
+pub use super::_entities::players::{ActiveModel, Column, Entity, Model};
+use super::player_aliases;
+use crate::steam::SteamId;
+
+#[async_trait]
 impl ActiveModelBehavior for ActiveModel {
-    // extend activemodel below (keep comment for generators)
+    async fn before_save<C>(mut self, db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if let ActiveValue::Set(Some(steam_id)) = &self.steam_id {
+            self.steam_id = ActiveValue::Set(normalize_steam_id(steam_id));
+        }
+        if insert {
+            return Ok(self);
+        }
+        let (ActiveValue::Set(Some(new_name)), ActiveValue::Unchanged(id) | ActiveValue::Set(id)) =
+            (&self.player_name, &self.id)
+        else {
+            return Ok(self);
+        };
+        // Keep the old name resolving after a rename
+        let old_name = Entity::find_by_id(*id)
+            .one(db)
+            .await?
+            .and_then(|player| player.player_name)
+            .filter(|old_name| !old_name.eq_ignore_ascii_case(new_name));
+        if let Some(old_name) = old_name {
+            player_aliases::rename(db, *id, &old_name, new_name).await?;
+        }
+        Ok(self)
+    }
+}
+
+/// A `steam_id` as stored: SteamID64 when it parses in any format, else
+/// trimmed, and `None` when blank.
+fn normalize_steam_id(steam_id: &str) -> Option<String> {
+    let trimmed = steam_id.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(
+        trimmed
+            .parse::<SteamId>()
+            .map_or_else(|_| trimmed.to_string(), |steam_id| steam_id.to_string()),
+    )
+}
+
+/// Rank tiers by minimum `current_elo`, highest first. Players below the
+/// last threshold are [`LOWEST_RANK_TIER`].
+pub const RANK_TIERS: [(i32, &str); 5] = [
+    (1400, "Diamond"),
+    (1250, "Platinum"),
+    (1100, "Gold"),
+    (950, "Silver"),
+    (800, "Bronze"),
+];
+pub const LOWEST_RANK_TIER: &str = "Iron";
+
+/// Case-insensitive `column = name` comparison built from `LOWER()`, so it
+/// renders the same on MySQL, Postgres and SQLite.
+pub fn name_eq<C: ColumnTrait>(column: C, name: &str) -> SimpleExpr {
+    Expr::expr(Func::lower(Expr::col((column.entity_name(), column)))).eq(Func::lower(Expr::val(name)))
+}
+
+/// Case-insensitive `column LIKE 'prefix%'`, with the `LIKE` wildcards in
+/// `prefix` taken literally.
+pub fn name_starts_with<C: ColumnTrait>(column: C, prefix: &str) -> SimpleExpr {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.to_lowercase().chars() {
+        if matches!(c, '!' | '%' | '_') {
+            pattern.push('!');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    Expr::expr(Func::lower(Expr::col((column.entity_name(), column))))
+        .like(LikeExpr::new(pattern).escape('!'))
+}
+
+impl Model {
+    /// Finds a player by name, ignoring case, or else by a name they went
+    /// by before.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn find_by_name<C: ConnectionTrait>(db: &C, name: &str) -> ModelResult<Option<Self>> {
+        let player = Entity::find()
+            .filter(name_eq(Column::PlayerName, name))
+            .one(db)
+            .await?;
+        if player.is_some() {
+            return Ok(player);
+        }
+        match player_aliases::find_player_id(db, name).await? {
+            Some(id) => Ok(Entity::find_by_id(id).one(db).await?),
+            None => Ok(None),
+        }
+    }
+
+    /// Finds a player by name, ignoring case, or else by Discord ID.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn find_by_name_or_discord_id<C: ConnectionTrait>(
+        db: &C,
+        key: &str,
+    ) -> ModelResult<Option<Self>> {
+        if let Some(player) = Self::find_by_name(db, key).await? {
+            return Ok(Some(player));
+        }
+        let player = Entity::find()
+            .filter(Column::DiscordId.eq(key))
+            .one(db)
+            .await?;
+        Ok(player)
+    }
+
+    /// Finds the player linked to a Steam account, whatever format the
+    /// stored `steam_id` is in. The oldest player wins when several share it;
+    /// see [`super::steam_report`] for those.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn find_by_steam_id<C: ConnectionTrait>(
+        db: &C,
+        steam_id: SteamId,
+    ) -> ModelResult<Option<Self>> {
+        let player = Entity::find()
+            .filter(Column::SteamId.is_in(steam_id.spellings()))
+            .order_by_asc(Column::Id)
+            .one(db)
+            .await?;
+        Ok(player)
+    }
+
+    /// The player's rank tier: `visual_rank_override` when set, otherwise
+    /// the [`RANK_TIERS`] entry for their `current_elo`.
+    pub fn rank_tier(&self) -> String {
+        if let Some(rank) = self
+            .visual_rank_override
+            .as_deref()
+            .map(str::trim)
+            .filter(|rank| !rank.is_empty())
+        {
+            return rank.to_string();
+        }
+        let elo = self.current_elo.unwrap_or_default();
+        RANK_TIERS
+            .iter()
+            .find(|(threshold, _)| elo >= *threshold)
+            .map_or(LOWEST_RANK_TIER, |(_, tier)| tier)
+            .to_string()
+    }
 }
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, Condition, IntoActiveModel, QueryOrder};
+use serde::Serialize;
+
+pub use super::_entities::seasons::{ActiveModel, Column, Entity, Model};
+use super::_entities::{match_participants, matches, players, season_standings};
+use super::matches::Record;
+use super::players::name_eq;
+use crate::elo::{self, EloConfig};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// A player's place in a season leaderboard.
+#[derive(Clone, Debug, Serialize)]
+pub struct Standing {
+    pub position: usize,
+    pub player_id: i32,
+    pub player_name: Option<String>,
+    pub rating: i32,
+    #[serde(flatten)]
+    pub record: Record,
+}
+
+impl Model {
+    /// Finds a season by name, ignoring case.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn find_by_name<C: ConnectionTrait>(db: &C, name: &str) -> ModelResult<Option<Self>> {
+        let season = Entity::find()
+            .filter(name_eq(Column::Name, name))
+            .one(db)
+            .await?;
+        Ok(season)
+    }
+
+    /// The season running now, the latest started one if they overlap.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn current<C: ConnectionTrait>(db: &C) -> ModelResult<Option<Self>> {
+        let now = Utc::now();
+        let season = Entity::find()
+            .filter(Column::StartsAt.lte(now))
+            .filter(
+                Condition::any()
+                    .add(Column::EndsAt.is_null())
+                    .add(Column::EndsAt.gt(now)),
+            )
+            .order_by_desc(Column::StartsAt)
+            .one(db)
+            .await?;
+        Ok(season)
+    }
+
+    /// Whether the season has an end date that has passed.
+    pub fn has_ended(&self) -> bool {
+        self.ends_at.is_some_and(|ends_at| ends_at <= Utc::now())
+    }
+
+    /// Selects the matches played during the season by `matches.created_at`.
+    /// The start is inclusive and the end exclusive.
+    pub fn match_condition(&self) -> Condition {
+        let mut condition = Condition::all().add(matches::Column::CreatedAt.gte(self.starts_at));
+        if let Some(ends_at) = self.ends_at {
+            condition = condition.add(matches::Column::CreatedAt.lt(ends_at));
+        }
+        condition
+    }
+
+    /// Whether `at` falls within the season.
+    pub fn contains(&self, at: DateTimeUtc) -> bool {
+        at >= self.starts_at && self.ends_at.is_none_or(|ends_at| at < ends_at)
+    }
+
+    /// The Elo settings of the season: `base` with ratings reset to the
+    /// season's starting rating.
+    pub fn elo_config(&self, base: &EloConfig) -> EloConfig {
+        EloConfig {
+            starting_rating: self.starting_rating,
+            ..base.clone()
+        }
+    }
+
+    /// The season leaderboard: the archived standings once the season is
+    /// archived, otherwise computed from its matches.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn standings<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        config: &EloConfig,
+    ) -> ModelResult<Vec<Standing>> {
+        if self.archived_at.is_some() {
+            self.archived_standings(db).await
+        } else {
+            self.live_standings(db, config).await
+        }
+    }
+
+    /// Replays the season's matches with every rating reset to the season's
+    /// starting rating, and ranks everyone who played by their final rating.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn live_standings<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        config: &EloConfig,
+    ) -> ModelResult<Vec<Standing>> {
+        let replay = elo::replay_matching(db, &self.elo_config(config), self.match_condition()).await?;
+
+        let played = match_participants::Entity::find()
+            .find_also_related(matches::Entity)
+            .filter(matches::Column::DeletedAt.is_null())
+            .filter(self.match_condition())
+            .all(db)
+            .await?;
+        let mut records: HashMap<i32, Record> = HashMap::new();
+        for (participant, match_item) in played {
+            let Some(match_item) = match_item else {
+                continue;
+            };
+            records
+                .entry(participant.player_id)
+                .or_default()
+                .add(match_item.result_for(&participant.team));
+        }
+
+        let player_ids = records
+            .keys()
+            .chain(replay.ratings.keys())
+            .copied()
+            .collect::<HashSet<_>>();
+        let names = player_names(db, player_ids.iter().copied()).await?;
+
+        let mut standings = player_ids
+            .into_iter()
+            .map(|player_id| Standing {
+                position: 0,
+                player_id,
+                player_name: names.get(&player_id).cloned().flatten(),
+                rating: replay
+                    .ratings
+                    .get(&player_id)
+                    .copied()
+                    .unwrap_or(self.starting_rating),
+                record: records.remove(&player_id).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        standings.sort_by(|a, b| {
+            b.rating
+                .cmp(&a.rating)
+                .then(b.record.games_played.cmp(&a.record.games_played))
+                .then(a.player_id.cmp(&b.player_id))
+        });
+        for (index, standing) in standings.iter_mut().enumerate() {
+            standing.position = index + 1;
+        }
+        Ok(standings)
+    }
+
+    /// The final standings written when the season was archived.
+    ///
+    /// # Errors
+    ///
+    /// When could not query the database
+    pub async fn archived_standings<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Vec<Standing>> {
+        let rows = season_standings::Entity::find()
+            .filter(season_standings::Column::SeasonId.eq(self.id))
+            .order_by_asc(season_standings::Column::Position)
+            .all(db)
+            .await?;
+        Ok(archived_rows(db, rows)
+            .await?
+            .into_iter()
+            .map(|(_, standing)| standing)
+            .collect())
+    }
+
+    /// Writes the season's live standings to `season_standings`, replacing
+    /// any earlier archive, and marks the season archived.
+    ///
+    /// # Errors
+    ///
+    /// When could not query or write to the database
+    pub async fn archive<C: ConnectionTrait>(
+        self,
+        db: &C,
+        config: &EloConfig,
+    ) -> ModelResult<(Self, Vec<Standing>)> {
+        let standings = self.live_standings(db, config).await?;
+
+        season_standings::Entity::delete_many()
+            .filter(season_standings::Column::SeasonId.eq(self.id))
+            .exec(db)
+            .await?;
+        let count = |value: usize| i32::try_from(value).unwrap_or(i32::MAX);
+        let rows = standings
+            .iter()
+            .map(|standing| season_standings::ActiveModel {
+                season_id: ActiveValue::set(self.id),
+                player_id: ActiveValue::set(standing.player_id),
+                position: ActiveValue::set(count(standing.position)),
+                rating: ActiveValue::set(standing.rating),
+                games_played: ActiveValue::set(count(standing.record.games_played)),
+                wins: ActiveValue::set(count(standing.record.wins)),
+                losses: ActiveValue::set(count(standing.record.losses)),
+                draws: ActiveValue::set(count(standing.record.draws)),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for chunk in rows.chunks(500) {
+            season_standings::Entity::insert_many(chunk.to_vec())
+                .exec(db)
+                .await?;
+        }
+
+        let mut season = self.into_active_model();
+        season.archived_at = ActiveValue::set(Some(Utc::now()));
+        let season = season.update(db).await?;
+        Ok((season, standings))
+    }
+}
+
+/// First place of every archived season, keyed by season id.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn champions<C: ConnectionTrait>(db: &C) -> ModelResult<HashMap<i32, Standing>> {
+    let rows = season_standings::Entity::find()
+        .filter(season_standings::Column::Position.eq(1))
+        .all(db)
+        .await?;
+    Ok(archived_rows(db, rows).await?.into_iter().collect())
+}
+
+/// Archived rows as standings, paired with their season id.
+async fn archived_rows<C: ConnectionTrait>(
+    db: &C,
+    rows: Vec<season_standings::Model>,
+) -> ModelResult<Vec<(i32, Standing)>> {
+    let names = player_names(db, rows.iter().map(|row| row.player_id)).await?;
+    let count = |value: i32| usize::try_from(value).unwrap_or_default();
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let standing = Standing {
+                position: count(row.position),
+                player_id: row.player_id,
+                player_name: names.get(&row.player_id).cloned().flatten(),
+                rating: row.rating,
+                record: Record::from_counts(count(row.wins), count(row.losses), count(row.draws)),
+            };
+            (row.season_id, standing)
+        })
+        .collect())
+}
+
+async fn player_names<C: ConnectionTrait>(
+    db: &C,
+    player_ids: impl IntoIterator<Item = i32>,
+) -> ModelResult<HashMap<i32, Option<String>>> {
+    let player_ids = player_ids.into_iter().collect::<Vec<_>>();
+    let mut names = HashMap::new();
+    for chunk in player_ids.chunks(1000) {
+        names.extend(
+            players::Entity::find()
+                .filter(players::Column::Id.is_in(chunk.iter().copied()))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|p| (p.id, p.player_name)),
+        );
+    }
+    Ok(names)
+}
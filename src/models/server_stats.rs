@@ -0,0 +1,163 @@
+//! Per-server aggregates of the `matches` history.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, Condition};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::matches;
+use super::matches::{DateRange, OutcomeSplit};
+
+/// Servers with fewer games are never flagged.
+pub const DEFAULT_MIN_GAMES: usize = 10;
+/// Draw rate above the all-server rate that counts as unusual.
+pub const DRAW_RATE_MARGIN: f64 = 0.15;
+/// Share of matches without an outcome that counts as many abandoned.
+pub const ABANDONED_RATE: f64 = 0.2;
+
+/// Query params of the server endpoints.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ServerParams {
+    pub game_type: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Games a server needs before it is flagged.
+    pub min_games: Option<usize>,
+}
+
+impl ServerParams {
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::all()
+            .add(matches::Column::DeletedAt.is_null())
+            .add(matches::Column::Server.is_not_null())
+            .add(
+                DateRange {
+                    from: self.from,
+                    to: self.to,
+                }
+                .condition(),
+            );
+        if let Some(game_type) = &self.game_type {
+            condition = condition.add(matches::Column::GameType.eq(game_type.as_str()));
+        }
+        condition
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerFlag {
+    /// Draws are well above the rate across all servers.
+    HighDrawRate,
+    /// Many matches never got an outcome.
+    ManyAbandoned,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ServerSummary {
+    pub server: String,
+    pub last_used: Option<DateTimeUtc>,
+    #[serde(flatten)]
+    pub outcomes: OutcomeSplit,
+    /// Share of matches without an outcome.
+    pub abandoned_rate: f64,
+    /// Games per map, most played first.
+    pub maps: Vec<MapCount>,
+    pub flags: Vec<ServerFlag>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MapCount {
+    pub map: String,
+    pub games: usize,
+}
+
+/// Summaries of every server used, most used first, flagged against the
+/// draw rate of all the servers together.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn summaries<C: ConnectionTrait>(
+    db: &C,
+    params: &ServerParams,
+) -> ModelResult<Vec<ServerSummary>> {
+    let played = matches::Entity::find()
+        .filter(params.condition())
+        .all(db)
+        .await?;
+    Ok(summarize(&played, params))
+}
+
+/// Summary of one server, matched ignoring case. `None` when the server
+/// was never used.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn detail<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+    params: &ServerParams,
+) -> ModelResult<Option<ServerSummary>> {
+    let played = matches::Entity::find()
+        .filter(params.condition())
+        .all(db)
+        .await?;
+    Ok(summarize(&played, params)
+        .into_iter()
+        .find(|summary| summary.server.eq_ignore_ascii_case(name)))
+}
+
+fn summarize(played: &[matches::Model], params: &ServerParams) -> Vec<ServerSummary> {
+    let mut overall = OutcomeSplit::default();
+    let mut servers: BTreeMap<&str, (ServerSummary, BTreeMap<&str, usize>)> = BTreeMap::new();
+    for item in played {
+        let Some(server) = item.server.as_deref() else {
+            continue;
+        };
+        overall.add(item);
+        let (summary, maps) = servers.entry(server).or_insert_with(|| {
+            (
+                ServerSummary {
+                    server: server.to_string(),
+                    ..Default::default()
+                },
+                BTreeMap::new(),
+            )
+        });
+        summary.outcomes.add(item);
+        summary.last_used = summary.last_used.max(Some(item.created_at));
+        *maps.entry(item.map.as_deref().unwrap_or("unknown")).or_default() += 1;
+    }
+
+    let min_games = params.min_games.unwrap_or(DEFAULT_MIN_GAMES);
+    let mut summaries = servers
+        .into_values()
+        .map(|(mut summary, maps)| {
+            let outcomes = &summary.outcomes;
+            summary.abandoned_rate = outcomes.undecided as f64 / outcomes.times_played as f64;
+            if outcomes.times_played >= min_games {
+                if outcomes.draw_rate > overall.draw_rate + DRAW_RATE_MARGIN {
+                    summary.flags.push(ServerFlag::HighDrawRate);
+                }
+                if summary.abandoned_rate > ABANDONED_RATE {
+                    summary.flags.push(ServerFlag::ManyAbandoned);
+                }
+            }
+            summary.maps = maps
+                .into_iter()
+                .map(|(map, games)| MapCount {
+                    map: map.to_string(),
+                    games,
+                })
+                .collect();
+            summary.maps.sort_by_key(|m| std::cmp::Reverse(m.games));
+            summary
+        })
+        .collect::<Vec<_>>();
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.outcomes.times_played));
+    summaries
+}
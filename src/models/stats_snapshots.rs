@@ -0,0 +1,136 @@
+//! The last download of each match's `stats_url`, kept so the stats page can
+//! be read back after the game server has rotated its logs.
+
+use chrono::Utc;
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel, TryIntoModel};
+use serde::Serialize;
+
+pub use super::_entities::stats_snapshots::{ActiveModel, Column, Entity, Model};
+use super::_entities::matches;
+use super::match_player_stats::{self, ImportSummary};
+use crate::stats_log::ParsedLog;
+
+/// `status` of a snapshot whose last download succeeded.
+pub const FETCHED: &str = "fetched";
+/// `status` of a snapshot whose last try failed; `body` and `summary` still
+/// hold the last successful download, if any.
+pub const FAILED: &str = "failed";
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// A downloaded page, before it is stored.
+#[derive(Clone, Debug)]
+pub struct Download {
+    pub url: String,
+    pub http_status: Option<i32>,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+/// What was read from a downloaded page, stored as `summary`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapshotSummary {
+    #[serde(flatten)]
+    pub log: ParsedLog,
+    /// `null` when the page was not a server log with player events, or when
+    /// the match's stats were uploaded by hand.
+    pub import: Option<ImportSummary>,
+    /// The match already had stats from an uploaded log, which were kept
+    /// instead of importing the page.
+    pub kept_upload: bool,
+}
+
+/// The snapshot of `match_id` to record a try of `url` on. `attempts` counts
+/// the tries since the last successful download of that url, across jobs.
+async fn for_try<C: ConnectionTrait>(db: &C, match_id: i32, url: &str) -> ModelResult<ActiveModel> {
+    let existing = for_match(db, match_id).await?;
+    let attempts = match &existing {
+        Some(item) if item.url == url && item.status != FETCHED => item.attempts.saturating_add(1),
+        _ => 1,
+    };
+    let mut item = existing.map_or_else(
+        || ActiveModel {
+            match_id: ActiveValue::set(match_id),
+            ..Default::default()
+        },
+        IntoActiveModel::into_active_model,
+    );
+    item.url = ActiveValue::set(url.to_string());
+    item.attempts = ActiveValue::set(attempts);
+    Ok(item)
+}
+
+/// Stores a successful download of `match_item`'s stats page. When the page
+/// is a server log, its per-player totals are imported as well, unless the
+/// match has stats from an uploaded log; those are kept and the summary says
+/// so.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn store_download<C: ConnectionTrait>(
+    db: &C,
+    match_item: &matches::Model,
+    download: Download,
+) -> ModelResult<Model> {
+    let log = ParsedLog::parse(&download.body);
+    let kept_upload =
+        !log.players.is_empty() && match_player_stats::has_upload(db, match_item.id).await?;
+    let import = if log.players.is_empty() || kept_upload {
+        None
+    } else {
+        Some(match_player_stats::import(db, match_item, &log, match_player_stats::DOWNLOADED).await?)
+    };
+    let summary = serde_json::to_value(SnapshotSummary {
+        log,
+        import,
+        kept_upload,
+    })
+    .map_err(|e| ModelError::Any(e.into()))?;
+
+    let mut item = for_try(db, match_item.id, &download.url).await?;
+    item.status = ActiveValue::set(FETCHED.to_string());
+    item.http_status = ActiveValue::set(download.http_status);
+    item.content_type = ActiveValue::set(download.content_type);
+    item.body = ActiveValue::set(Some(download.body));
+    item.summary = ActiveValue::set(Some(summary));
+    item.error = ActiveValue::set(None);
+    item.fetched_at = ActiveValue::set(Some(Utc::now()));
+    Ok(item.save(db).await?.try_into_model()?)
+}
+
+/// Records a failed try of downloading `url` for `match_id`, keeping the body
+/// of an earlier successful download.
+///
+/// # Errors
+///
+/// When could not query or write to the database
+pub async fn store_failure<C: ConnectionTrait>(
+    db: &C,
+    match_id: i32,
+    url: &str,
+    http_status: Option<i32>,
+    error: &str,
+) -> ModelResult<Model> {
+    let mut item = for_try(db, match_id, url).await?;
+    item.status = ActiveValue::set(FAILED.to_string());
+    item.http_status = ActiveValue::set(http_status);
+    item.error = ActiveValue::set(Some(error.to_string()));
+    Ok(item.save(db).await?.try_into_model()?)
+}
+
+/// The snapshot of `match_id` (`matches.id`), if its stats page was ever
+/// downloaded.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn for_match<C: ConnectionTrait>(db: &C, match_id: i32) -> ModelResult<Option<Model>> {
+    Ok(Entity::find()
+        .filter(Column::MatchId.eq(match_id))
+        .one(db)
+        .await?)
+}
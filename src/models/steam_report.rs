@@ -0,0 +1,112 @@
+//! Report of `players.steam_id` values that need a person to look at them:
+//! Steam accounts linked to more than one player, typically a Discord
+//! account and a stats-only account created for the same person, and values
+//! that are not a Steam ID at all.
+
+use std::collections::{BTreeMap, HashMap};
+
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, QueryOrder, QuerySelect};
+use serde::Serialize;
+
+use super::_entities::{match_participants, players};
+use crate::steam::{SteamFormats, SteamId};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LinkedPlayer {
+    pub id: i32,
+    pub player_name: Option<String>,
+    pub discord_id: Option<String>,
+    /// `steam_id` as stored, which may be in any format.
+    pub stored_steam_id: String,
+    pub games_played: usize,
+    pub deleted: bool,
+}
+
+/// Players sharing one Steam account.
+#[derive(Clone, Debug, Serialize)]
+pub struct SharedSteamId {
+    pub steam_id: SteamFormats,
+    pub players: Vec<LinkedPlayer>,
+    /// The player to keep when merging: the non-deleted one with a Discord
+    /// ID and the most games.
+    pub suggested_primary: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SteamReport {
+    pub shared: Vec<SharedSteamId>,
+    /// Players whose `steam_id` is not a Steam ID.
+    pub invalid: Vec<LinkedPlayer>,
+    /// Players whose `steam_id` is valid but not stored as SteamID64.
+    pub unnormalized: Vec<LinkedPlayer>,
+}
+
+/// Groups every player with a `steam_id` by Steam account and reports the
+/// shared, invalid and unnormalized ones.
+///
+/// # Errors
+///
+/// When could not query the database
+pub async fn report<C: ConnectionTrait>(db: &C) -> ModelResult<SteamReport> {
+    let linked = players::Entity::find()
+        .filter(players::Column::SteamId.is_not_null())
+        .order_by_asc(players::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut games: HashMap<i32, usize> = HashMap::new();
+    for ids in linked.iter().map(|p| p.id).collect::<Vec<_>>().chunks(1000) {
+        for player_id in match_participants::Entity::find()
+            .select_only()
+            .column(match_participants::Column::PlayerId)
+            .filter(match_participants::Column::PlayerId.is_in(ids.iter().copied()))
+            .into_tuple::<i32>()
+            .all(db)
+            .await?
+        {
+            *games.entry(player_id).or_default() += 1;
+        }
+    }
+
+    let mut report = SteamReport::default();
+    let mut accounts: BTreeMap<SteamId, Vec<LinkedPlayer>> = BTreeMap::new();
+    for player in linked {
+        let stored = player.steam_id.clone().unwrap_or_default();
+        if stored.trim().is_empty() {
+            continue;
+        }
+        let entry = LinkedPlayer {
+            id: player.id,
+            player_name: player.player_name,
+            discord_id: player.discord_id,
+            games_played: games.get(&player.id).copied().unwrap_or_default(),
+            deleted: player.deleted_at.is_some(),
+            stored_steam_id: stored.clone(),
+        };
+        match stored.parse::<SteamId>() {
+            Ok(steam_id) => {
+                if stored != steam_id.to_string() {
+                    report.unnormalized.push(entry.clone());
+                }
+                accounts.entry(steam_id).or_default().push(entry);
+            }
+            Err(_) => report.invalid.push(entry),
+        }
+    }
+
+    report.shared = accounts
+        .into_iter()
+        .filter(|(_, players)| players.len() > 1)
+        .map(|(steam_id, players)| SharedSteamId {
+            steam_id: steam_id.into(),
+            suggested_primary: players
+                .iter()
+                .filter(|p| !p.deleted)
+                .max_by_key(|p| (p.discord_id.is_some(), p.games_played, std::cmp::Reverse(p.id)))
+                .map(|p| p.id),
+            players,
+        })
+        .collect();
+    Ok(report)
+}
@@ -0,0 +1,116 @@
+use std::{fmt, str::FromStr};
+
+use async_trait::async_trait;
+use loco_rs::{
+    auth::jwt,
+    hash,
+    model::{Authenticable, ModelError, ModelResult},
+};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub use super::_entities::users::{self, ActiveModel, Entity, Model};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// What a user is allowed to do. Roles are ordered: every role can do
+/// everything the roles below it can.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access to protected endpoints.
+    Viewer,
+    /// The Discord bot: reports matches.
+    Bot,
+    /// Full access, including notes and player moderation.
+    Admin,
+}
+
+impl FromStr for Role {
+    type Err = ModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(Self::Viewer),
+            "bot" => Ok(Self::Bot),
+            "admin" => Ok(Self::Admin),
+            other => Err(ModelError::Any(format!("unknown role `{other}`").into())),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Viewer => "viewer",
+            Self::Bot => "bot",
+            Self::Admin => "admin",
+        })
+    }
+}
+
+#[async_trait]
+impl Authenticable for Model {
+    async fn find_by_api_key(db: &DatabaseConnection, api_key: &str) -> ModelResult<Self> {
+        let user = Entity::find()
+            .filter(users::Column::ApiKey.eq(api_key))
+            .one(db)
+            .await?;
+        user.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    async fn find_by_claims_key(db: &DatabaseConnection, claims_key: &str) -> ModelResult<Self> {
+        Self::find_by_pid(db, claims_key).await
+    }
+}
+
+impl Model {
+    /// Finds a user by the `pid` carried in their JWT.
+    ///
+    /// # Errors
+    ///
+    /// When could not find user or DB query error
+    pub async fn find_by_pid(db: &DatabaseConnection, pid: &str) -> ModelResult<Self> {
+        let pid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let user = Entity::find()
+            .filter(users::Column::Pid.eq(pid))
+            .one(db)
+            .await?;
+        user.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Finds a user by email.
+    ///
+    /// # Errors
+    ///
+    /// When could not find user or DB query error
+    pub async fn find_by_email(db: &DatabaseConnection, email: &str) -> ModelResult<Self> {
+        let user = Entity::find()
+            .filter(users::Column::Email.eq(email))
+            .one(db)
+            .await?;
+        user.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// The user's role. Unknown values in the database grant nothing beyond
+    /// [`Role::Viewer`].
+    pub fn role(&self) -> Role {
+        self.role.parse().unwrap_or(Role::Viewer)
+    }
+
+    #[must_use]
+    pub fn verify_password(&self, password: &str) -> bool {
+        hash::verify_password(password, &self.password)
+    }
+
+    /// Issues a JWT for this user.
+    ///
+    /// # Errors
+    ///
+    /// when could not convert user claims to jwt token
+    pub fn generate_jwt(&self, secret: &str, expiration: &u64) -> ModelResult<String> {
+        Ok(jwt::JWT::new(secret).generate_token(expiration, self.pid.to_string(), None)?)
+    }
+}
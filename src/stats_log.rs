@@ -0,0 +1,195 @@
+//! Parser for Half-Life server logs as written by TFC servers and read by
+//! HLStats, e.g.
+//!
+//! ```text
+//! L 10/01/2024 - 20:05:12: "Ed<2><STEAM_0:0:1001><Blue>" killed "Kevin<5><STEAM_0:0:1005><Red>" with "rocket"
+//! L 10/01/2024 - 20:06:40: "Edd<3><STEAM_0:0:1002><Blue>" triggered "Team 1 dropoff"
+//! ```
+//!
+//! Lines the parser does not know are skipped, so whole server logs can be
+//! fed in as they are.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::steam::SteamId;
+
+/// `triggered` actions that count as a flag capture. TFC maps name their
+/// capture points freely, so anything ending in one of these counts.
+const CAPTURE_SUFFIXES: [&str; 3] = ["dropoff", "capture", "cap"];
+/// `triggered` actions that count as picking up the flag.
+const FLAG_TOUCH_ACTIONS: [&str; 2] = ["Red Flag", "Blue Flag"];
+
+/// A player as the log names them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogPlayer {
+    pub name: String,
+    /// `None` for bots and for servers that log `STEAM_ID_LAN` or
+    /// `STEAM_ID_PENDING`.
+    pub steam_id: Option<SteamId>,
+    pub team: Option<String>,
+}
+
+impl LogPlayer {
+    /// Parses `Name<uid><STEAM_0:0:1001><Blue>`, the part between quotes.
+    pub fn parse(token: &str) -> Option<Self> {
+        let (rest, team) = split_tag(token)?;
+        let (rest, steam_id) = split_tag(rest)?;
+        let (name, _uid) = split_tag(rest)?;
+        Some(Self {
+            name: name.to_string(),
+            steam_id: steam_id.parse().ok(),
+            team: Some(team)
+                .filter(|team| !team.is_empty() && !team.eq_ignore_ascii_case("unassigned"))
+                .map(str::to_string),
+        })
+    }
+}
+
+/// Splits `rest<tag>` into `rest` and `tag`.
+fn split_tag(token: &str) -> Option<(&str, &str)> {
+    let inner = token.strip_suffix('>')?;
+    let open = inner.rfind('<')?;
+    Some((&inner[..open], &inner[open + 1..]))
+}
+
+/// One player's totals over a log.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PlayerLine {
+    pub name: String,
+    #[serde(skip)]
+    pub steam_id: Option<SteamId>,
+    /// The last team the player was seen on.
+    pub team: Option<String>,
+    pub kills: i32,
+    pub deaths: i32,
+    pub suicides: i32,
+    pub teamkills: i32,
+    pub flag_caps: i32,
+    pub flag_touches: i32,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ParsedLog {
+    pub map: Option<String>,
+    pub started_at: Option<NaiveDateTime>,
+    pub ended_at: Option<NaiveDateTime>,
+    /// In order of first appearance.
+    pub players: Vec<PlayerLine>,
+    /// Lines that looked like log lines but had no event the parser knows.
+    pub skipped_lines: usize,
+}
+
+impl ParsedLog {
+    /// Parses a whole log file.
+    pub fn parse(log: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for line in log.lines() {
+            let Some((at, event)) = split_timestamp(line.trim_end()) else {
+                continue;
+            };
+            parsed.started_at = parsed.started_at.or(at);
+            parsed.ended_at = at.or(parsed.ended_at);
+            if !parsed.apply(event, &mut index) {
+                parsed.skipped_lines += 1;
+            }
+        }
+        parsed
+    }
+
+    fn player(&mut self, player: &LogPlayer, index: &mut HashMap<String, usize>) -> &mut PlayerLine {
+        // Players without a Steam ID are told apart by name
+        let key = player
+            .steam_id
+            .map_or_else(|| format!("name:{}", player.name), |id| id.to_string());
+        let position = *index.entry(key).or_insert_with(|| {
+            self.players.push(PlayerLine {
+                name: player.name.clone(),
+                steam_id: player.steam_id,
+                ..Default::default()
+            });
+            self.players.len() - 1
+        });
+        let line = &mut self.players[position];
+        line.name.clone_from(&player.name);
+        if player.team.is_some() {
+            line.team.clone_from(&player.team);
+        }
+        line
+    }
+
+    /// Applies one event, returning whether it was understood.
+    fn apply(&mut self, event: &str, index: &mut HashMap<String, usize>) -> bool {
+        for prefix in ["Started map ", "Loading map "] {
+            if let Some(map) = event.strip_prefix(prefix) {
+                self.map = unquote(map.split_whitespace().next().unwrap_or_default());
+                return self.map.is_some();
+            }
+        }
+
+        let Some(rest) = event.strip_prefix('"') else {
+            return false;
+        };
+        if let Some((actor, victim)) = rest.split_once("\" killed \"") {
+            let Some((victim, _weapon)) = victim.split_once("\" with \"") else {
+                return false;
+            };
+            let (Some(actor), Some(victim)) = (LogPlayer::parse(actor), LogPlayer::parse(victim))
+            else {
+                return false;
+            };
+            let teamkill = actor.team.is_some() && actor.team == victim.team;
+            let line = self.player(&actor, index);
+            if teamkill {
+                line.teamkills += 1;
+            } else {
+                line.kills += 1;
+            }
+            self.player(&victim, index).deaths += 1;
+            return true;
+        }
+        if let Some((actor, _weapon)) = rest.split_once("\" committed suicide with \"") {
+            let Some(actor) = LogPlayer::parse(actor) else {
+                return false;
+            };
+            let line = self.player(&actor, index);
+            line.suicides += 1;
+            line.deaths += 1;
+            return true;
+        }
+        if let Some((actor, action)) = rest.split_once("\" triggered \"") {
+            let Some(actor) = LogPlayer::parse(actor) else {
+                return false;
+            };
+            let action = action.split('"').next().unwrap_or_default();
+            let lower = action.to_lowercase();
+            let line = self.player(&actor, index);
+            if CAPTURE_SUFFIXES.iter().any(|suffix| lower.ends_with(suffix)) {
+                line.flag_caps += 1;
+            } else if FLAG_TOUCH_ACTIONS.iter().any(|a| a.eq_ignore_ascii_case(action)) {
+                line.flag_touches += 1;
+            }
+            return true;
+        }
+        if let Some((actor, _team)) = rest.split_once("\" joined team \"") {
+            // The player tag still shows the old team; the next event has the new one
+            return LogPlayer::parse(actor).is_some();
+        }
+        false
+    }
+}
+
+/// Splits `L 10/01/2024 - 20:05:12: event` into its time and event. Lines
+/// without the `L ` prefix are not log lines.
+fn split_timestamp(line: &str) -> Option<(Option<NaiveDateTime>, &str)> {
+    let (stamp, event) = line.strip_prefix("L ")?.split_once(": ")?;
+    Some((NaiveDateTime::parse_from_str(stamp, "%m/%d/%Y - %H:%M:%S").ok(), event))
+}
+
+fn unquote(value: &str) -> Option<String> {
+    let value = value.trim_matches('"');
+    (!value.is_empty()).then(|| value.to_string())
+}
@@ -0,0 +1,141 @@
+//! Steam account IDs in the three formats players and game servers use.
+//!
+//! - SteamID64, e.g. `76561197960267730`, used by the Steam web API
+//! - SteamID2, e.g. `STEAM_0:0:1001`, written by GoldSrc servers such as TFC
+//! - SteamID3, e.g. `[U:1:2002]`
+//!
+//! All three encode the same 32-bit account number of an individual account
+//! in the public universe. [`SteamId`] parses any of them, and SteamID64 is
+//! the form stored in `players.steam_id`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+/// SteamID64 of account number 0 of an individual account.
+const STEAM64_BASE: u64 = 76_561_197_960_265_728;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidSteamId(pub String);
+
+impl fmt::Display for InvalidSteamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a SteamID64, STEAM_X:Y:Z or [U:1:Z] Steam ID",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidSteamId {}
+
+/// An individual Steam account, by its account number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SteamId(u32);
+
+impl SteamId {
+    pub fn account_id(self) -> u32 {
+        self.0
+    }
+
+    pub fn steam64(self) -> u64 {
+        STEAM64_BASE + u64::from(self.0)
+    }
+
+    /// SteamID2 with universe 0, the way GoldSrc servers log it.
+    pub fn steam2(self) -> String {
+        format!("STEAM_0:{}:{}", self.0 % 2, self.0 / 2)
+    }
+
+    pub fn steam3(self) -> String {
+        format!("[U:1:{}]", self.0)
+    }
+
+    /// The stored values that mean this account: every format, including
+    /// SteamID2 with universe 1 and SteamID3 without brackets.
+    pub fn spellings(self) -> Vec<String> {
+        vec![
+            self.steam64().to_string(),
+            self.steam2(),
+            format!("STEAM_1:{}:{}", self.0 % 2, self.0 / 2),
+            self.steam3(),
+            format!("U:1:{}", self.0),
+        ]
+    }
+}
+
+impl FromStr for SteamId {
+    type Err = InvalidSteamId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let invalid = || InvalidSteamId(s.to_string());
+
+        if let Some(rest) = trimmed
+            .strip_prefix("STEAM_")
+            .or_else(|| trimmed.strip_prefix("steam_"))
+        {
+            let mut parts = rest.split(':');
+            let (Some(universe), Some(low), Some(high), None) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return Err(invalid());
+            };
+            if !matches!(universe, "0" | "1") {
+                return Err(invalid());
+            }
+            let low: u32 = low.parse().map_err(|_| invalid())?;
+            let high: u32 = high.parse().map_err(|_| invalid())?;
+            if low > 1 {
+                return Err(invalid());
+            }
+            return high
+                .checked_mul(2)
+                .and_then(|account| account.checked_add(low))
+                .map(Self)
+                .ok_or_else(invalid);
+        }
+
+        let bracketed = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .unwrap_or(trimmed);
+        if let Some(account) = bracketed.strip_prefix("U:1:") {
+            return account.parse().map(Self).map_err(|_| invalid());
+        }
+
+        let steam64: u64 = trimmed.parse().map_err(|_| invalid())?;
+        steam64
+            .checked_sub(STEAM64_BASE)
+            .and_then(|account| u32::try_from(account).ok())
+            .map(Self)
+            .ok_or_else(invalid)
+    }
+}
+
+/// Displays as SteamID64, the stored form.
+impl fmt::Display for SteamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.steam64())
+    }
+}
+
+/// A Steam ID in every format, for API responses.
+#[derive(Clone, Debug, Serialize)]
+pub struct SteamFormats {
+    pub steam64: String,
+    pub steam2: String,
+    pub steam3: String,
+}
+
+impl From<SteamId> for SteamFormats {
+    fn from(id: SteamId) -> Self {
+        Self {
+            steam64: id.steam64().to_string(),
+            steam2: id.steam2(),
+            steam3: id.steam3(),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! This task archives the final standings of seasons, so the leaderboard of
+//! a closed season no longer depends on later changes to the match history.
+//!
+//! # Example
+//!
+//! Archive every season that has ended and is not archived yet:
+//! ```sh
+//! cargo loco task archive_season
+//! ```
+//!
+//! Archive (or re-archive) one season by name:
+//! ```sh
+//! cargo loco task archive_season "name:Season 1"
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::{elo::EloConfig, models::seasons};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct ArchiveSeason;
+#[async_trait]
+impl Task for ArchiveSeason {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "archive_season".to_string(),
+            detail: "Archive the final standings of ended seasons (or name:<season>)".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let config = EloConfig::from_context(app_context)?;
+
+        let pending = match vars.cli_arg("name") {
+            Ok(name) => vec![seasons::Model::find_by_name(&app_context.db, name)
+                .await?
+                .ok_or_else(|| Error::string(&format!("season `{name}` not found")))?],
+            Err(_) => seasons::Entity::find()
+                .filter(seasons::Column::ArchivedAt.is_null())
+                .all(&app_context.db)
+                .await?
+                .into_iter()
+                .filter(seasons::Model::has_ended)
+                .collect(),
+        };
+
+        for season in pending {
+            let txn = app_context.db.begin().await?;
+            let (season, standings) = season.archive(&txn, &config).await?;
+            txn.commit().await?;
+            tracing::info!(
+                season = season.name,
+                players = standings.len(),
+                champion = standings.first().and_then(|s| s.player_name.as_deref()),
+                "archived season"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! This task prints how well the stored win probabilities are calibrated:
+//! Brier score and log-loss overall, per map and over time, and a
+//! reliability table of predicted against observed blue win rates.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task calibration_report
+//! cargo loco task calibration_report game_type:4v4 from:2024-09-01 buckets:5 period:week
+//! ```
+
+use chrono::NaiveDate;
+use loco_rs::prelude::*;
+
+use crate::models::{
+    calibration::{self, CalibrationParams, Period},
+    matches::PredictionRecord,
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct CalibrationReport;
+#[async_trait]
+impl Task for CalibrationReport {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "calibration_report".to_string(),
+            detail: "Print Brier score, log-loss and a reliability table of the match predictions"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let date = |name: &str| {
+            vars.cli_arg(name)
+                .ok()
+                .map(|value| {
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                        Error::string(&format!("{name} must be a YYYY-MM-DD date, got `{value}`"))
+                    })
+                })
+                .transpose()
+        };
+        let params = CalibrationParams {
+            game_type: vars.cli_arg("game_type").ok().cloned(),
+            from: date("from")?,
+            to: date("to")?,
+            buckets: vars
+                .cli_arg("buckets")
+                .ok()
+                .map(|value| {
+                    value.parse().map_err(|_| {
+                        Error::string(&format!("buckets must be a number, got `{value}`"))
+                    })
+                })
+                .transpose()?,
+            period: match vars.cli_arg("period").map(String::as_str) {
+                Ok("week") => Period::Week,
+                Ok("month") | Err(_) => Period::Month,
+                Ok(other) => {
+                    return Err(Error::string(&format!(
+                        "unknown period `{other}`, expected `week` or `month`"
+                    )))
+                }
+            },
+        };
+
+        let report = calibration::report(&app_context.db, &params).await?;
+        tracing::info!(overall = %scores(&report.overall), "calibration");
+        for bucket in &report.buckets {
+            tracing::info!(
+                predicted = %format!("{:.2}-{:.2}", bucket.lower, bucket.upper),
+                matches = bucket.matches,
+                mean_predicted = %rate(bucket.mean_predicted),
+                observed = %rate(bucket.observed),
+                "calibration bucket"
+            );
+        }
+        for map in &report.by_map {
+            tracing::info!(map = %map.map, record = %scores(&map.record), "calibration by map");
+        }
+        for period in &report.by_period {
+            tracing::info!(
+                period = %period.period,
+                record = %scores(&period.record),
+                "calibration by period"
+            );
+        }
+        Ok(())
+    }
+}
+
+fn rate(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{value:.3}"))
+}
+
+fn scores(record: &PredictionRecord) -> String {
+    format!(
+        "{} matches, brier {}, log-loss {}, accuracy {}",
+        record.rated,
+        rate(record.brier_score),
+        rate(record.log_loss),
+        rate(record.accuracy)
+    )
+}
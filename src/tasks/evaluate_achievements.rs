@@ -0,0 +1,33 @@
+//! This task replays the match history and awards the achievements players
+//! earned since it last ran. Run it on a schedule or after importing matches;
+//! achievements already awarded are left alone.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task evaluate_achievements
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::models::achievements;
+
+#[allow(clippy::module_name_repetitions)]
+pub struct EvaluateAchievements;
+#[async_trait]
+impl Task for EvaluateAchievements {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "evaluate_achievements".to_string(),
+            detail: "Award the achievements earned in the match history".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &task::Vars) -> Result<()> {
+        let txn = app_context.db.begin().await?;
+        let awarded = achievements::evaluate(&txn).await?;
+        txn.commit().await?;
+        tracing::info!(awarded, "evaluated achievements");
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+//! This task marks penalties whose `expires_at` has passed as expired.
+//! Expired penalties already stop counting once their time is up; marking
+//! them keeps the history apart from revoked ones. Run it on a schedule,
+//! e.g. hourly from cron.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task expire_penalties
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::models::player_penalties;
+
+#[allow(clippy::module_name_repetitions)]
+pub struct ExpirePenalties;
+#[async_trait]
+impl Task for ExpirePenalties {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "expire_penalties".to_string(),
+            detail: "Mark penalties past their expiry as expired".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &task::Vars) -> Result<()> {
+        let expired = player_penalties::expire(&app_context.db).await?;
+        tracing::info!(expired, "expired penalties");
+        Ok(())
+    }
+}
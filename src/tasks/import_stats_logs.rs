@@ -0,0 +1,96 @@
+//! This task imports server logs into the per-player match stats. Each log
+//! file is named after the bot's match number (`matches.match_id`), e.g.
+//! `101.log`; importing a match again replaces its stats.
+//!
+//! # Example
+//!
+//! Import every `*.log` file in a directory:
+//! ```sh
+//! cargo loco task import_stats_logs dir:logs/
+//! ```
+//!
+//! Import one file into a given match:
+//! ```sh
+//! cargo loco task import_stats_logs file:logs/L1001000.log match:101
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use loco_rs::prelude::*;
+
+use crate::{
+    models::{_entities::matches, match_player_stats},
+    stats_log::ParsedLog,
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct ImportStatsLogs;
+#[async_trait]
+impl Task for ImportStatsLogs {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "import_stats_logs".to_string(),
+            detail: "Import server logs into match player stats (dir:<path> or file:<path> match:<number>)"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let files = match (vars.cli_arg("dir"), vars.cli_arg("file")) {
+            (Ok(dir), _) => log_files(Path::new(dir))?,
+            (Err(_), Ok(file)) => {
+                let number = vars
+                    .cli_arg("match")
+                    .map_err(|_| Error::string("file: needs a match:<number> too"))?;
+                vec![(PathBuf::from(file), number.clone())]
+            }
+            (Err(_), Err(_)) => return Err(Error::string("give dir:<path> or file:<path>")),
+        };
+
+        for (path, number) in files {
+            let Ok(number) = number.parse::<i32>() else {
+                tracing::warn!(path = %path.display(), "skipping log not named after a match number");
+                continue;
+            };
+            let Some(match_item) = matches::Entity::find()
+                .filter(matches::Column::MatchId.eq(number))
+                .one(&app_context.db)
+                .await?
+            else {
+                tracing::warn!(path = %path.display(), number, "no such match");
+                continue;
+            };
+
+            let log = ParsedLog::parse(&std::fs::read_to_string(&path)?);
+            let txn = app_context.db.begin().await?;
+            let summary =
+                match_player_stats::import(&txn, &match_item, &log, match_player_stats::UPLOADED)
+                    .await?;
+            txn.commit().await?;
+            tracing::info!(
+                path = %path.display(),
+                number,
+                imported = summary.imported,
+                unmatched = ?summary.unmatched,
+                map_mismatch = summary.map_mismatch,
+                "imported stats log"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `*.log` files in `dir` with their file stem, sorted by name.
+fn log_files(dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = std::fs::read_dir(dir)?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_string();
+            Some((path, stem))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
@@ -1 +1,9 @@
+pub mod archive_season;
+pub mod calibration_report;
+pub mod evaluate_achievements;
+pub mod expire_penalties;
+pub mod import_stats_logs;
+pub mod recompute_elo;
 pub mod seed;
+pub mod sync_match_participants;
+pub mod sync_player_aliases;
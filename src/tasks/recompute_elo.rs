@@ -0,0 +1,193 @@
+//! This task replays the match history through the Elo engine in
+//! [`crate::elo`], to audit or replace the ratings written by the bot.
+//!
+//! # Example
+//!
+//! Compare the replayed ratings against the stored ones (the default, read
+//! only):
+//! ```sh
+//! cargo loco task recompute_elo
+//! cargo loco task recompute_elo mode:diff game_type:4v4
+//! ```
+//!
+//! Throw away the stored `player_elo` history and `players.current_elo`
+//! values and write the replayed ones instead. This always replays every game
+//! type, since the ratings carry over between them:
+//! ```sh
+//! cargo loco task recompute_elo mode:rebuild
+//! ```
+
+use std::collections::HashMap;
+
+use loco_rs::prelude::*;
+use sea_orm::sea_query::Expr;
+
+use crate::{
+    elo::{self, EloConfig, Replay},
+    models::_entities::{player_elo, players},
+};
+
+/// Team ranks and probabilities are stored as `f32`, so allow for rounding.
+const TOLERANCE: f64 = 0.01;
+
+#[allow(clippy::module_name_repetitions)]
+pub struct RecomputeElo;
+#[async_trait]
+impl Task for RecomputeElo {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "recompute_elo".to_string(),
+            detail: "Replay match history through the Elo engine (mode:diff or mode:rebuild)"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let config = EloConfig::from_context(app_context)?;
+        let game_type = vars.cli_arg("game_type").ok().map(String::as_str);
+        let mode = vars.cli_arg("mode").map_or("diff", String::as_str);
+        if mode == "rebuild" && game_type.is_some() {
+            // A rebuild replaces the whole history, which a filtered replay
+            // only covers part of
+            return Err(Error::string("mode:rebuild cannot be limited to a game_type"));
+        }
+        let replay = elo::replay(&app_context.db, &config, game_type).await?;
+
+        match mode {
+            "rebuild" => rebuild(&app_context.db, &replay).await,
+            "diff" => diff(&app_context.db, &replay).await,
+            other => Err(Error::string(&format!(
+                "unknown mode `{other}`, expected `diff` or `rebuild`"
+            ))),
+        }
+    }
+}
+
+async fn rebuild(db: &DatabaseConnection, replay: &Replay) -> Result<()> {
+    let txn = db.begin().await?;
+
+    player_elo::Entity::delete_many().exec(&txn).await?;
+    let entries = replay
+        .matches
+        .iter()
+        .flat_map(elo::RatedMatch::elo_entries)
+        .collect::<Vec<_>>();
+    for chunk in entries.chunks(500) {
+        player_elo::Entity::insert_many(chunk.to_vec())
+            .exec(&txn)
+            .await?;
+    }
+
+    for (&player_id, &rating) in &replay.ratings {
+        players::Entity::update_many()
+            .col_expr(players::Column::CurrentElo, Expr::value(rating))
+            .filter(players::Column::Id.eq(player_id))
+            .exec(&txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+    tracing::info!(
+        entries = entries.len(),
+        matches = replay.matches.len(),
+        players = replay.ratings.len(),
+        "rebuilt player_elo history"
+    );
+    Ok(())
+}
+
+async fn diff(db: &DatabaseConnection, replay: &Replay) -> Result<()> {
+    // Stored ratings keyed by (discord_id, match_id)
+    let stored: HashMap<(i64, i64), i32> = player_elo::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|e| Some(((e.discord_id?, e.match_id?), e.player_elos?)))
+        .collect();
+
+    let mut match_mismatches = 0;
+    let mut entry_mismatches = 0;
+    for rated in &replay.matches {
+        let m = &rated.stored;
+        let label = m.match_id.map_or_else(|| format!("#{}", m.id), |id| id.to_string());
+
+        let ranks = [
+            ("blue_rank", m.blue_rank, rated.rating.blue_rank),
+            ("red_rank", m.red_rank, rated.rating.red_rank),
+            ("blue_probability", m.blue_probability, rated.rating.blue_probability),
+        ];
+        for (column, stored, computed) in ranks {
+            let Some(stored) = stored.map(f64::from) else {
+                continue;
+            };
+            let tolerance = if column == "blue_probability" {
+                TOLERANCE
+            } else {
+                1.0
+            };
+            if (stored - computed).abs() > tolerance {
+                match_mismatches += 1;
+                tracing::warn!(
+                    number = %label,
+                    column,
+                    stored,
+                    computed,
+                    "team rating mismatch"
+                );
+            }
+        }
+
+        for entry in rated.elo_entries() {
+            let (
+                ActiveValue::Set(Some(discord_id)),
+                ActiveValue::Set(Some(match_id)),
+                ActiveValue::Set(Some(computed)),
+            ) = (entry.discord_id, entry.match_id, entry.player_elos)
+            else {
+                continue;
+            };
+            match stored.get(&(discord_id, match_id)) {
+                Some(&stored) if stored == computed => {}
+                Some(&stored) => {
+                    entry_mismatches += 1;
+                    tracing::warn!(
+                        number = %label,
+                        discord_id,
+                        stored,
+                        computed,
+                        "player_elo mismatch"
+                    );
+                }
+                None => {
+                    entry_mismatches += 1;
+                    tracing::warn!(number = %label, discord_id, "player_elo entry missing");
+                }
+            }
+        }
+    }
+
+    let mut player_mismatches = 0;
+    for player in players::Entity::find().all(db).await? {
+        let Some(&computed) = replay.ratings.get(&player.id) else {
+            continue;
+        };
+        if player.current_elo != Some(computed) {
+            player_mismatches += 1;
+            tracing::warn!(
+                player = player.player_name.as_deref().unwrap_or("?"),
+                stored = player.current_elo,
+                computed,
+                "current_elo mismatch"
+            );
+        }
+    }
+
+    tracing::info!(
+        matches = replay.matches.len(),
+        match_mismatches,
+        entry_mismatches,
+        player_mismatches,
+        "replayed match history"
+    );
+    Ok(())
+}
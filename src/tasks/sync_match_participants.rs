@@ -0,0 +1,55 @@
+//! This task rebuilds `match_participants` rows from the comma-joined
+//! `blue_team`/`red_team` rosters, for matches that were written straight to
+//! the database by the bot.
+//!
+//! # Example
+//!
+//! Sync only the matches that have no participants yet:
+//! ```sh
+//! cargo loco task sync_match_participants
+//! ```
+//!
+//! Rebuild the participants of every match:
+//! ```sh
+//! cargo loco task sync_match_participants all:true
+//! ```
+
+use loco_rs::prelude::*;
+use sea_orm::QuerySelect;
+
+use crate::models::_entities::{match_participants, matches};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct SyncMatchParticipants;
+#[async_trait]
+impl Task for SyncMatchParticipants {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "sync_match_participants".to_string(),
+            detail: "Rebuild match participants from the match roster strings".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let all = vars.cli_arg("all").is_ok_and(|all| all == "true");
+
+        let mut query = matches::Entity::find();
+        if !all {
+            let synced = match_participants::Entity::find()
+                .select_only()
+                .column(match_participants::Column::MatchId)
+                .distinct()
+                .into_tuple::<i32>()
+                .all(&app_context.db)
+                .await?;
+            query = query.filter(matches::Column::Id.is_not_in(synced));
+        }
+
+        let pending = query.all(&app_context.db).await?;
+        for match_item in &pending {
+            match_participants::ActiveModel::sync_for_match(&app_context.db, match_item).await?;
+        }
+        tracing::info!(matches = pending.len(), "synced match participants");
+        Ok(())
+    }
+}
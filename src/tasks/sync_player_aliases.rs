@@ -0,0 +1,32 @@
+//! This task records the former names found in the Elo history as player
+//! aliases, so players renamed before aliases were kept, or renamed by the
+//! bot writing to the database directly, still resolve by their old names.
+//! Running it again only refreshes when each name was last used.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task sync_player_aliases
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::models::player_aliases;
+
+#[allow(clippy::module_name_repetitions)]
+pub struct SyncPlayerAliases;
+#[async_trait]
+impl Task for SyncPlayerAliases {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "sync_player_aliases".to_string(),
+            detail: "Record former player names from the Elo history as aliases".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &task::Vars) -> Result<()> {
+        let names = player_aliases::backfill_from_elo_history(&app_context.db).await?;
+        tracing::info!(names, "synced player aliases");
+        Ok(())
+    }
+}
@@ -1,8 +1,62 @@
+//! Downloads a match's `stats_url` into a [`stats_snapshots`] row. Each job
+//! tries once; while the stats server is unreachable or busy the job fails,
+//! and the queue retries it with its own backoff.
+
 use std::time::Duration;
 
 use loco_rs::prelude::*;
 use serde::{Deserialize, Serialize};
-use tokio::time::sleep;
+
+use crate::models::_entities::matches;
+use crate::models::stats_snapshots::{self, Download};
+
+/// Download tuning, read from the `settings.stats_download` section of the
+/// app config.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DownloadConfig {
+    pub timeout_secs: u64,
+    /// Pages larger than this are not stored.
+    pub max_bytes: usize,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 20,
+            max_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+impl DownloadConfig {
+    /// Reads `settings.stats_download` from the app config, using the
+    /// defaults for anything that is not set.
+    ///
+    /// # Errors
+    ///
+    /// When the `settings.stats_download` section does not deserialize
+    pub fn from_context(ctx: &AppContext) -> Result<Self> {
+        match ctx
+            .config
+            .settings
+            .as_ref()
+            .and_then(|s| s.get("stats_download"))
+        {
+            Some(config) => Ok(serde_json::from_value(config.clone())?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Why one try failed.
+#[derive(Debug)]
+struct Failure {
+    http_status: Option<i32>,
+    message: String,
+    /// Whether trying again might help: network errors, 5xx and 429.
+    retry: bool,
+}
 
 pub struct DownloadWorker {
     pub ctx: AppContext,
@@ -10,7 +64,8 @@ pub struct DownloadWorker {
 
 #[derive(Deserialize, Debug, Serialize)]
 pub struct DownloadWorkerArgs {
-    pub user_guid: String,
+    /// `matches.id` of the match to download the stats page of.
+    pub match_id: i32,
 }
 
 impl worker::AppWorker<DownloadWorkerArgs> for DownloadWorker {
@@ -22,14 +77,114 @@ impl worker::AppWorker<DownloadWorkerArgs> for DownloadWorker {
 #[async_trait]
 impl worker::Worker<DownloadWorkerArgs> for DownloadWorker {
     async fn perform(&self, args: DownloadWorkerArgs) -> worker::Result<()> {
-        // TODO: Some actual work goes here...
-        println!("================================================");
-        println!("Sending payment report to user {}", args.user_guid);
+        // The failure is already on the snapshot; failing the job hands the
+        // retry over to the queue
+        self.download(args.match_id).await.map_err(|err| {
+            tracing::warn!(match_id = args.match_id, error = %err, "stats download failed");
+            Box::<dyn std::error::Error + Send + Sync>::from(err.to_string()).into()
+        })
+    }
+}
 
-        sleep(Duration::from_millis(2000)).await;
+impl DownloadWorker {
+    /// One try at `match_id`'s stats page. Fails when trying again might
+    /// help; a page that cannot be downloaded is only recorded.
+    async fn download(&self, match_id: i32) -> Result<()> {
+        let config = DownloadConfig::from_context(&self.ctx)?;
+        let Some(match_item) = matches::Entity::find_by_id(match_id).one(&self.ctx.db).await?
+        else {
+            tracing::warn!(match_id, "stats download for a match that no longer exists");
+            return Ok(());
+        };
+        let Some(url) = match_item.stats_url.clone().filter(|url| !url.trim().is_empty()) else {
+            return Ok(());
+        };
 
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::string(&e.to_string()))?;
 
-        println!("================================================");
-        Ok(())
+        match fetch(&client, &url, &config).await {
+            Ok((http_status, content_type, body)) => {
+                let txn = self.ctx.db.begin().await?;
+                stats_snapshots::store_download(
+                    &txn,
+                    &match_item,
+                    Download {
+                        url,
+                        http_status: Some(http_status),
+                        content_type,
+                        body,
+                    },
+                )
+                .await?;
+                txn.commit().await?;
+                Ok(())
+            }
+            Err(failure) => {
+                stats_snapshots::store_failure(
+                    &self.ctx.db,
+                    match_id,
+                    &url,
+                    failure.http_status,
+                    &failure.message,
+                )
+                .await?;
+                if failure.retry {
+                    return Err(Error::string(&failure.message));
+                }
+                tracing::warn!(match_id, error = %failure.message, "giving up on stats download");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// One try: the status, content type and body of a successful response.
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    config: &DownloadConfig,
+) -> std::result::Result<(i32, Option<String>, String), Failure> {
+    let network = |err: reqwest::Error| Failure {
+        http_status: None,
+        message: err.to_string(),
+        retry: true,
+    };
+    let response = client.get(url).send().await.map_err(network)?;
+    let status = response.status();
+    let http_status = Some(i32::from(status.as_u16()));
+    if !status.is_success() {
+        return Err(Failure {
+            http_status,
+            message: format!("{url} answered {status}"),
+            retry: status.is_server_error() || status.as_u16() == 429,
+        });
+    }
+    let too_large = || Failure {
+        http_status,
+        message: format!("{url} is larger than {} bytes", config.max_bytes),
+        retry: false,
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > config.max_bytes as u64)
+    {
+        return Err(too_large());
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response.bytes().await.map_err(network)?;
+    if body.len() > config.max_bytes {
+        return Err(too_large());
     }
+    Ok((
+        i32::from(status.as_u16()),
+        content_type,
+        String::from_utf8_lossy(&body).into_owned(),
+    ))
 }
@@ -0,0 +1,32 @@
+use tfpugs_web_app::{
+    app::App,
+    models::{_entities::players, achievements},
+};
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn awards_achievements_once() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+
+    // Six players won a match. Only well6 had been played when Ed, Edd,
+    // Eddy and Rolf won 101, so that was every map at the time
+    assert_eq!(achievements::evaluate(db).await.unwrap(), 10);
+    assert_eq!(achievements::evaluate(db).await.unwrap(), 0);
+
+    let edd = players::Model::find_by_name(db, "Edd").await.unwrap().unwrap();
+    let unlocked = achievements::for_player(db, edd.id).await.unwrap();
+    let keys = unlocked
+        .iter()
+        .map(|u| u.achievement.key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["first_win", "every_map"]);
+    assert_eq!(unlocked[0].match_id, Some(1));
+    assert_eq!(unlocked[1].match_id, Some(1));
+
+    let ed = players::Model::find_by_name(db, "Ed").await.unwrap().unwrap();
+    assert_eq!(achievements::for_player(db, ed.id).await.unwrap().len(), 2);
+}
@@ -0,0 +1,40 @@
+use tfpugs_web_app::balance::{balance, BalanceError, Constraints};
+
+#[test]
+fn prefers_the_closest_split() {
+    let splits = balance(&[1400, 1000, 1200, 1200], &Constraints::default(), 10).unwrap();
+
+    // Mirrored splits are only listed once, with the first player on blue
+    assert_eq!(splits.len(), 3);
+    assert_eq!(splits[0].blue, [0, 1]);
+    assert!((splits[0].blue_probability - 0.5).abs() < f64::EPSILON);
+    assert!(splits[1].imbalance() <= splits[2].imbalance());
+}
+
+#[test]
+fn follows_constraints() {
+    let constraints = Constraints {
+        apart: vec![(0, 1)],
+        together: vec![(2, 3)],
+        captains: Some((1, 0)),
+    };
+    let splits = balance(&[1400, 1000, 1200, 1200, 900, 900], &constraints, 10).unwrap();
+    for split in &splits {
+        assert!(split.blue.contains(&1) && split.red.contains(&0));
+        assert_eq!(split.blue.contains(&2), split.blue.contains(&3));
+    }
+
+    assert_eq!(
+        balance(&[1000, 1000, 1000], &Constraints::default(), 1).unwrap_err(),
+        BalanceError::PlayerCount(3)
+    );
+    let constraints = Constraints {
+        together: vec![(0, 1)],
+        captains: Some((0, 1)),
+        ..Default::default()
+    };
+    assert_eq!(
+        balance(&[1000, 1000], &constraints, 1).unwrap_err(),
+        BalanceError::Unsatisfiable
+    );
+}
@@ -0,0 +1,52 @@
+use tfpugs_web_app::{
+    app::App,
+    elo::{self, EloConfig, Engine},
+};
+use loco_rs::testing;
+use serial_test::serial;
+
+#[test]
+fn even_teams_split_the_k_factor() {
+    let mut engine = Engine::new(EloConfig::default());
+
+    let rating = engine.rate(&[1, 2], &[3, 4], 1.0);
+    assert!((rating.blue_probability - 0.5).abs() < f64::EPSILON);
+    assert_eq!(rating.blue[0].change(), 16);
+    assert_eq!(rating.red[0].change(), -16);
+    assert_eq!(engine.rating(1), 1016);
+    assert_eq!(engine.rating(3), 984);
+}
+
+#[test]
+fn draws_move_ratings_towards_each_other() {
+    let mut engine = Engine::new(EloConfig {
+        k_factor: 40.0,
+        starting_rating: 1500,
+    });
+    engine.rate(&[1], &[2], 1.0);
+
+    let rating = engine.rate(&[1], &[2], 0.5);
+    assert!(rating.blue_probability > 0.5);
+    assert!(rating.blue[0].change() < 0);
+    assert_eq!(rating.red[0].change(), -rating.blue[0].change());
+}
+
+#[tokio::test]
+#[serial]
+async fn can_replay_match_history() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let replay = elo::replay(&boot.app_context.db, &EloConfig::default(), Some("4v4"))
+        .await
+        .unwrap();
+    assert_eq!(replay.matches.len(), 3);
+
+    // Ed: won match 1 (+16), lost match 2 (-16), drew match 3 as the
+    // slightly stronger team (-1).
+    assert_eq!(replay.ratings[&1], 999);
+    assert_eq!(replay.ratings[&4], 1001);
+
+    let entries = replay.matches[0].elo_entries();
+    assert_eq!(entries.len(), 8);
+}
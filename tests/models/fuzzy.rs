@@ -0,0 +1,26 @@
+use tfpugs_web_app::{
+    fuzzy,
+    models::player_search::{match_name, MatchKind},
+};
+
+#[test]
+fn measures_edit_distance_and_trigrams() {
+    assert_eq!(fuzzy::levenshtein("kitten", "sitting"), 3);
+    assert_eq!(fuzzy::levenshtein("", "ed"), 2);
+    assert!((fuzzy::trigram_similarity("Plank", "plank") - 1.0).abs() < f64::EPSILON);
+    assert!(fuzzy::trigram_similarity("plank", "sarah") < 0.1);
+    assert!(fuzzy::similarity("kevn", "Kevin") >= 0.8);
+}
+
+#[test]
+fn exact_beats_prefix_beats_fuzzy() {
+    let exact = match_name("edd", "Edd").unwrap();
+    let prefix = match_name("edd", "Eddy").unwrap();
+    let fuzzy = match_name("edd", "Ed").unwrap();
+    assert_eq!(
+        (exact.0, prefix.0, fuzzy.0),
+        (MatchKind::Exact, MatchKind::Prefix, MatchKind::Fuzzy)
+    );
+    assert!(exact.1 > prefix.1 && prefix.1 > fuzzy.1);
+    assert!(match_name("edd", "Sarah").is_none());
+}
@@ -0,0 +1,48 @@
+use tfpugs_web_app::live::{LiveEvent, LiveFeed, BACKLOG};
+
+fn reorder() -> LiveEvent {
+    LiveEvent::LeaderboardReordered { top: vec![] }
+}
+
+fn ids(feed: &LiveFeed, last_event_id: Option<u64>) -> Option<Vec<u64>> {
+    feed.subscribe(last_event_id)
+        .missed
+        .map(|missed| missed.into_iter().map(|e| e.id).collect())
+}
+
+#[test]
+fn resumes_after_the_last_event_id() {
+    let feed = LiveFeed::default();
+    for _ in 0..3 {
+        feed.publish(reorder());
+    }
+    assert_eq!(ids(&feed, None), Some(vec![]));
+    assert_eq!(ids(&feed, Some(1)), Some(vec![2, 3]));
+    assert_eq!(ids(&feed, Some(3)), Some(vec![]));
+    // An id from before a restart
+    assert_eq!(ids(&feed, Some(7)), None);
+}
+
+#[test]
+fn asks_for_a_reload_once_events_are_dropped() {
+    let feed = LiveFeed::default();
+    for _ in 0..BACKLOG + 2 {
+        feed.publish(reorder());
+    }
+    assert_eq!(ids(&feed, Some(1)), None);
+    assert_eq!(ids(&feed, Some(2)).map(|ids| ids.len()), Some(BACKLOG));
+}
+
+#[test]
+fn subscribers_receive_new_events_once() {
+    let feed = LiveFeed::default();
+    feed.publish(reorder());
+    let mut subscription = feed.subscribe(Some(0));
+    assert_eq!(subscription.missed.as_ref().map(Vec::len), Some(1));
+
+    let id = feed.publish(reorder());
+    let received = subscription.receiver.try_recv().unwrap();
+    assert_eq!(received.id, id);
+    assert_eq!(received.event.kind(), "leaderboard_reordered");
+    assert!(subscription.receiver.try_recv().is_err());
+}
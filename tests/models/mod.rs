@@ -0,0 +1,12 @@
+mod achievements;
+mod balance;
+mod elo;
+mod fuzzy;
+mod live;
+mod player_aliases;
+mod player_elos;
+mod player_penalties;
+mod players;
+mod seasons;
+mod stats_log;
+mod steam;
@@ -0,0 +1,38 @@
+use loco_rs::{prelude::*, testing};
+use serial_test::serial;
+use tfpugs_web_app::{
+    app::App,
+    models::{_entities::player_elo, player_aliases, players},
+};
+
+#[tokio::test]
+#[serial]
+async fn backfills_former_names_from_elo_history() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+
+    // Kevin played match 100 as "Kev"
+    player_elo::ActiveModel {
+        match_id: Set(Some(100)),
+        player_name: Set(Some("Kev".to_string())),
+        player_elos: Set(Some(1230)),
+        discord_id: Set(Some(200_000_000_000_000_005)),
+        created_at: Set(Some("2024-09-30T21:00:00Z".parse().unwrap())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    assert_eq!(player_aliases::backfill_from_elo_history(db).await.unwrap(), 1);
+    assert_eq!(player_aliases::backfill_from_elo_history(db).await.unwrap(), 1);
+    let aliases = player_aliases::for_player(db, 5).await.unwrap();
+    assert_eq!(
+        aliases.iter().map(|a| a.alias.as_str()).collect::<Vec<_>>(),
+        ["Kev"]
+    );
+
+    let kevin = players::Model::find_by_name(db, "KEV").await.unwrap().unwrap();
+    assert_eq!(kevin.id, 5);
+}
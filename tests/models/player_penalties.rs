@@ -0,0 +1,22 @@
+use tfpugs_web_app::{app::App, models::player_penalties};
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn expires_penalties_past_their_end() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+
+    // Only Sarah's week-long penalty has run out; Ed's was revoked instead
+    assert_eq!(player_penalties::expire(db).await.unwrap(), 1);
+    assert_eq!(player_penalties::expire(db).await.unwrap(), 0);
+
+    let history = player_penalties::Model::history(db, 7).await.unwrap();
+    assert_eq!(history[0].expired_at, history[0].expires_at);
+    assert!(history[0].revoked_at.is_none());
+
+    let dunced = player_penalties::dunced_players(db).await.unwrap();
+    assert_eq!(dunced.into_iter().collect::<Vec<_>>(), [8]);
+}
@@ -0,0 +1,25 @@
+use loco_rs::{prelude::*, testing};
+use serial_test::serial;
+use tfpugs_web_app::{app::App, models::players, steam::SteamId};
+
+#[tokio::test]
+#[serial]
+async fn stores_steam_ids_as_steam64() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+
+    let plank = players::Model::find_by_name(db, "Plank").await.unwrap().unwrap();
+    let mut item = plank.into_active_model();
+    item.steam_id = Set(Some(" steam_0:1:4000 ".to_string()));
+    let plank = item.update(db).await.unwrap();
+    assert_eq!(plank.steam_id.as_deref(), Some("76561197960273729"));
+
+    let steam_id = "[U:1:8001]".parse::<SteamId>().unwrap();
+    let found = players::Model::find_by_steam_id(db, steam_id).await.unwrap();
+    assert_eq!(found.map(|p| p.id), Some(plank.id));
+
+    let mut item = plank.into_active_model();
+    item.steam_id = Set(Some("  ".to_string()));
+    assert_eq!(item.update(db).await.unwrap().steam_id, None);
+}
@@ -0,0 +1,51 @@
+use tfpugs_web_app::{app::App, elo::EloConfig, models::seasons};
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn season_standings_reset_ratings() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+    let config = EloConfig::default();
+
+    // Season 2 holds matches 102 (red win) and 103 (draw) only
+    let season = seasons::Model::find_by_name(db, "season 2")
+        .await
+        .unwrap()
+        .unwrap();
+    let standings = season.live_standings(db, &config).await.unwrap();
+    assert_eq!(standings.len(), 8);
+    assert_eq!(standings[0].player_name.as_deref(), Some("Eddy"));
+    assert_eq!(standings[0].rating, 1017);
+    assert_eq!(standings[0].record.games_played, 2);
+    // Ed lost 102 and drew 103 on the side the ratings favoured
+    assert_eq!(standings.last().unwrap().rating, 983);
+}
+
+#[tokio::test]
+#[serial]
+async fn archiving_keeps_the_final_standings() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+    let config = EloConfig::default();
+
+    let season = seasons::Model::find_by_name(db, "Season 1")
+        .await
+        .unwrap()
+        .unwrap();
+    let (season, standings) = season.archive(db, &config).await.unwrap();
+    assert!(season.archived_at.is_some());
+    assert_eq!(standings[0].player_name.as_deref(), Some("Ed"));
+    assert_eq!(standings[0].rating, 1016);
+
+    let archived = season.standings(db, &config).await.unwrap();
+    assert_eq!(archived.len(), standings.len());
+    assert_eq!(archived[0].player_id, standings[0].player_id);
+    assert_eq!(archived[0].record.wins, 1);
+
+    let champions = seasons::champions(db).await.unwrap();
+    assert_eq!(champions[&season.id].player_name.as_deref(), Some("Ed"));
+}
@@ -0,0 +1,37 @@
+use tfpugs_web_app::stats_log::{LogPlayer, ParsedLog};
+
+const LOG_101: &str = include_str!("../../src/fixtures/logs/101.log");
+
+#[test]
+fn parses_player_tags() {
+    let player = LogPlayer::parse("Ed <the> Great<2><STEAM_0:0:1001><Blue>").unwrap();
+    assert_eq!(player.name, "Ed <the> Great");
+    assert_eq!(player.steam_id.unwrap().steam2(), "STEAM_0:0:1001");
+    assert_eq!(player.team.as_deref(), Some("Blue"));
+
+    let bot = LogPlayer::parse("Bot<12><BOT><Unassigned>").unwrap();
+    assert!(bot.steam_id.is_none() && bot.team.is_none());
+    assert!(LogPlayer::parse("no tags").is_none());
+}
+
+#[test]
+fn totals_a_match_log() {
+    let log = ParsedLog::parse(LOG_101);
+    assert_eq!(log.map.as_deref(), Some("well6"));
+    assert_eq!(log.started_at.unwrap().to_string(), "2024-10-01 19:40:01");
+    assert_eq!(log.ended_at.unwrap().to_string(), "2024-10-01 20:00:01");
+    assert_eq!(log.players.len(), 9);
+
+    let line = |name: &str| log.players.iter().find(|p| p.name == name).unwrap();
+    assert_eq!(line("Edd").flag_caps, 2);
+    assert_eq!(line("Edd").flag_touches, 2);
+    assert_eq!(line("Ed").kills, 2);
+    assert_eq!(line("Kevin").deaths, 2);
+    assert_eq!(line("Kevin").team.as_deref(), Some("Red"));
+    // Sarah's grenade hit her own team
+    assert_eq!(line("Sarah").kills, 0);
+    assert_eq!(line("Sarah").teamkills, 1);
+    assert_eq!(line("Jimmy").deaths, 2);
+    assert_eq!(line("Jimmy").suicides, 1);
+    assert!(line("Guest").steam_id.is_none());
+}
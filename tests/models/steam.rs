@@ -0,0 +1,37 @@
+use tfpugs_web_app::steam::SteamId;
+
+#[test]
+fn parses_every_format_to_the_same_account() {
+    let ids = [
+        "76561197960267731",
+        "STEAM_0:1:1001",
+        "STEAM_1:1:1001",
+        "[U:1:2003]",
+        "U:1:2003",
+        " 76561197960267731 ",
+    ]
+    .map(|s| s.parse::<SteamId>().unwrap());
+    assert!(ids.iter().all(|id| *id == ids[0]));
+
+    let id = ids[0];
+    assert_eq!(id.account_id(), 2003);
+    assert_eq!(id.to_string(), "76561197960267731");
+    assert_eq!(id.steam2(), "STEAM_0:1:1001");
+    assert_eq!(id.steam3(), "[U:1:2003]");
+}
+
+#[test]
+fn rejects_malformed_ids() {
+    for invalid in [
+        "",
+        "1001",
+        "STEAM_0:2:1001",
+        "STEAM_5:0:1001",
+        "STEAM_0:0",
+        "[G:1:2003]",
+        "76561197960265727",
+        "99999999999999999999",
+    ] {
+        assert!(invalid.parse::<SteamId>().is_err(), "{invalid}");
+    }
+}
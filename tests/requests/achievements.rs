@@ -0,0 +1,31 @@
+use tfpugs_web_app::{app::App, models::achievements};
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_list_achievements() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        achievements::evaluate(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/achievements").await;
+        assert_eq!(res.status_code(), 200);
+        let list = res.json::<serde_json::Value>();
+        assert_eq!(list.as_array().unwrap().len(), achievements::DEFINITIONS.len());
+        assert_eq!(list[0]["key"], "first_win");
+        assert_eq!(list[0]["unlocked_by"], 6);
+
+        let res = request.get("/api/players/eddy/achievements").await;
+        assert_eq!(res.status_code(), 200);
+        let unlocked = res.json::<serde_json::Value>();
+        assert_eq!(unlocked[1]["key"], "every_map");
+        assert_eq!(unlocked[1]["unlocked_at"], "2024-10-01T20:00:00Z");
+
+        let res = request.get("/api/players/plank/achievements").await;
+        assert_eq!(res.json::<serde_json::Value>(), serde_json::json!([]));
+        let res = request.get("/api/players/nobody/achievements").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
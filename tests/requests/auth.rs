@@ -0,0 +1,86 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_login_and_use_the_token() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .post("/api/auth/login")
+            .json(&serde_json::json!({
+                "email": "admin@example.com",
+                "password": "12341234",
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let login = res.json::<serde_json::Value>();
+        assert_eq!(login["role"], "admin");
+
+        let token = login["token"].as_str().unwrap();
+        let res = request
+            .get("/api/auth/current")
+            .add_header(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+            )
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["email"], "admin@example.com");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn login_rejects_a_wrong_password() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .post("/api/auth/login")
+            .json(&serde_json::json!({
+                "email": "admin@example.com",
+                "password": "wrong",
+            }))
+            .await;
+        assert_eq!(res.status_code(), 401);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn writing_notes_requires_an_admin() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({ "title": "hello" });
+        let res = request.post("/api/notes").json(&payload).await;
+        assert_eq!(res.status_code(), 401);
+
+        let res = request
+            .post("/api/notes")
+            .add_header(
+                AUTHORIZATION,
+                HeaderValue::from_static("Bearer lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758"),
+            )
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 403);
+
+        let res = request
+            .post("/api/notes")
+            .add_header(
+                AUTHORIZATION,
+                HeaderValue::from_static("Bearer lo-3c0a8f5e-2d7b-4f0e-9a61-5b8e2f9d7c14"),
+            )
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 200);
+    })
+    .await;
+}
@@ -0,0 +1,52 @@
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+fn approx(value: &serde_json::Value, expected: f64) -> bool {
+    (value.as_f64().unwrap() - expected).abs() < 1e-4
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_calibration_report() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/calibration")
+            .add_query_param("buckets", "2")
+            .add_query_param("period", "week")
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let report = res.json::<serde_json::Value>();
+
+        // Blue at 0.55 won, blue at 0.52 lost and blue at 0.48 drew
+        assert_eq!(report["overall"]["rated"], 3);
+        assert!(approx(&report["overall"]["brier_score"], (0.2025 + 0.2704 + 0.0004) / 3.0));
+        let log_loss = -(0.55f64.ln() + 0.48f64.ln() + 0.5 * (0.48f64.ln() + 0.52f64.ln())) / 3.0;
+        assert!(approx(&report["overall"]["log_loss"], log_loss));
+
+        let buckets = report["buckets"].as_array().unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0]["matches"], 1);
+        assert_eq!(buckets[0]["observed"], 0.5);
+        assert_eq!(buckets[1]["matches"], 2);
+        assert!(approx(&buckets[1]["mean_predicted"], 0.535));
+        assert_eq!(buckets[1]["observed"], 0.5);
+
+        assert_eq!(report["by_map"][0]["map"], "well6");
+        assert_eq!(report["by_map"][0]["rated"], 2);
+        assert_eq!(report["by_period"][0]["period"], "2024-W40");
+        assert_eq!(report["by_period"][0]["rated"], 3);
+
+        let res = request
+            .get("/api/calibration")
+            .add_query_param("from", "2024-10-03")
+            .await;
+        let report = res.json::<serde_json::Value>();
+        assert_eq!(report["overall"]["rated"], 1);
+        assert_eq!(report["buckets"].as_array().unwrap().len(), 10);
+        assert_eq!(report["by_period"][0]["period"], "2024-10");
+    })
+    .await;
+}
@@ -0,0 +1,59 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
+use loco_rs::testing;
+use serial_test::serial;
+use tfpugs_web_app::{app::App, live};
+
+const BOT_API_KEY: &str = "lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758";
+
+fn bearer(token: &str) -> HeaderValue {
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn reporting_a_match_publishes_live_events() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let mut subscription = live::feed().subscribe(None);
+
+        // Ed overtakes Kevin at the top of the leaderboard
+        let payload = serde_json::json!({
+            "blue_team": ["100000000000000001", "100000000000000002"],
+            "red_team": ["200000000000000005", "200000000000000006"],
+            "match_outcome": 1,
+        });
+        let res = request
+            .post("/api/matches")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let id = res.json::<serde_json::Value>()["match_data"]["id"].clone();
+
+        let mut received = vec![];
+        while let Ok(envelope) = subscription.receiver.try_recv() {
+            received.push(envelope);
+        }
+        let kinds = received.iter().map(|e| e.event.kind()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            ["match_created", "match_finished", "elo_changed", "leaderboard_reordered"]
+        );
+        let top = serde_json::to_value(&received[3].event).unwrap();
+        assert_eq!(top["top"][0]["player_name"], "Ed");
+
+        // A map correction changes nothing the feed reports
+        let res = request
+            .patch(&format!("/api/matches/{id}"))
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "map": "2fort" }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert!(subscription.receiver.try_recv().is_err());
+
+        // A client that saw the first event gets the other three on reconnect
+        let resumed = live::feed().subscribe(Some(received[0].id));
+        assert_eq!(resumed.missed.map(|missed| missed.len()), Some(3));
+    })
+    .await;
+}
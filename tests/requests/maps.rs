@@ -0,0 +1,61 @@
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_list_maps() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/maps").await;
+        assert_eq!(res.status_code(), 200);
+        let maps = res.json::<serde_json::Value>();
+        assert_eq!(maps.as_array().unwrap().len(), 2);
+
+        // well6: a 3:1 blue win the ratings saw coming, then a 1:1 draw
+        let well6 = &maps[0];
+        assert_eq!(well6["map"], "well6");
+        assert_eq!(well6["times_played"], 2);
+        assert_eq!(well6["blue_wins"], 1);
+        assert_eq!(well6["draw_rate"], 0.5);
+        assert_eq!(well6["average_winning_score"], 2.0);
+        assert_eq!(well6["prediction"]["predicted"], 1);
+        assert_eq!(well6["prediction"]["accuracy"], 1.0);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_map_with_top_players() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/maps/WELL6")
+            .add_query_param("min_games", "2")
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let map = res.json::<serde_json::Value>();
+        assert_eq!(map["map"], "well6");
+        let top = map["top_players"].as_array().unwrap();
+        assert_eq!(top.len(), 8);
+        assert_eq!(top[0]["player_name"], "Ed");
+        assert_eq!(top[0]["winrate"], 0.5);
+        assert_eq!(top[7]["winrate"], 0.0);
+
+        let res = request
+            .get("/api/maps/well6")
+            .add_query_param("min_games", "3")
+            .await;
+        assert!(res.json::<serde_json::Value>()["top_players"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+
+        let res = request.get("/api/maps/nowhere").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
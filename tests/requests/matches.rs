@@ -1,7 +1,15 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
 use tfpugs_web_app::app::App;
 use loco_rs::testing;
 use serial_test::serial;
 
+const BOT_API_KEY: &str = "lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758";
+const VIEWER_API_KEY: &str = "lo-153561ca-fa84-4e1b-813a-c62526d0a77e";
+
+fn bearer(token: &str) -> HeaderValue {
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
 #[tokio::test]
 #[serial]
 async fn can_get_echo() {
@@ -10,7 +18,7 @@ async fn can_get_echo() {
             "foo": "bar",
         });
 
-        let res = request.post("/matches/echo").json(&payload).await;
+        let res = request.post("/api/matches/echo").json(&payload).await;
         assert_eq!(res.status_code(), 200);
         assert_eq!(res.text(), serde_json::to_string(&payload).unwrap());
     })
@@ -20,10 +28,414 @@ async fn can_get_echo() {
 #[tokio::test]
 #[serial]
 async fn can_request_root() {
-    testing::request::<App, _, _>(|request, _ctx| async move {
-        let res = request.get("/matches").await;
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/matches").await;
+        assert_eq!(res.status_code(), 200);
+        let page = res.json::<serde_json::Value>();
+        assert_eq!(page["items"].as_array().unwrap().len(), 3);
+        assert_eq!(page["total"], 3);
+        assert!(page["next_cursor"].is_null());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_page_through_matches() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/matches")
+            .add_query_param("sort", "match_id")
+            .add_query_param("order", "asc")
+            .add_query_param("limit", "2")
+            .await;
+        let page = res.json::<serde_json::Value>();
+        let ids = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["match_id"].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![101, 102]);
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["next_cursor"], 2);
+
+        let res = request
+            .get("/api/matches")
+            .add_query_param("sort", "match_id")
+            .add_query_param("order", "asc")
+            .add_query_param("limit", "2")
+            .add_query_param("cursor", "2")
+            .await;
+        let page = res.json::<serde_json::Value>();
+        assert_eq!(page["items"][0]["match_id"], 103);
+        assert!(page["next_cursor"].is_null());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_filter_matches() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let total = |page: serde_json::Value| page["total"].as_u64().unwrap();
+
+        let res = request
+            .get("/api/matches")
+            .add_query_param("map", "well6")
+            .await;
+        assert_eq!(total(res.json()), 2);
+        let res = request
+            .get("/api/matches")
+            .add_query_param("map", "well6")
+            .add_query_param("outcome", "draw")
+            .await;
+        assert_eq!(total(res.json()), 1);
+        let res = request
+            .get("/api/matches")
+            .add_query_param("server", "West")
+            .add_query_param("game_type", "4v4")
+            .await;
+        assert_eq!(total(res.json()), 1);
+        let res = request
+            .get("/api/matches")
+            .add_query_param("from", "2024-10-02")
+            .add_query_param("to", "2024-10-02")
+            .await;
+        assert_eq!(total(res.json()), 1);
+        let res = request
+            .get("/api/matches")
+            .add_query_param("player", "plank")
+            .await;
+        assert_eq!(total(res.json()), 0);
+        let res = request
+            .get("/api/matches/with-players")
+            .add_query_param("player", "eddy")
+            .add_query_param("outcome", "red")
+            .await;
+        let page = res.json::<serde_json::Value>();
+        assert_eq!(page["total"], 1);
+        assert_eq!(page["items"][0]["match_data"]["match_id"], 102);
+
+        // Typing part of a name or former name narrows the list down
+        let res = request
+            .get("/api/matches")
+            .add_query_param("player", "jimmy")
+            .await;
+        let jimmy = total(res.json());
+        assert!(jimmy > 0);
+        let res = request
+            .get("/api/matches")
+            .add_query_param("player", "jimb")
+            .await;
+        assert_eq!(total(res.json()), jimmy);
+        let res = request
+            .get("/api/matches")
+            .add_query_param("player", "e%")
+            .await;
+        assert_eq!(total(res.json()), 0);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_matches_by_player_name() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/matches/player/rolf").await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<Vec<serde_json::Value>>().len(), 3);
+
+        let res = request.get("/api/matches/player/plank").await;
+        assert_eq!(res.status_code(), 200);
+        assert!(res.json::<Vec<serde_json::Value>>().is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_matches_with_players() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/matches/with-players").await;
+        assert_eq!(res.status_code(), 200);
+        let page = res.json::<serde_json::Value>();
+        let first = page["items"]
+            .as_array()
+            .unwrap()
+            .iter().find(|m| m["match_data"]["id"] == 1).unwrap();
+        let blue = first["blue_team_players"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["player_name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(blue, vec!["Ed", "Edd", "Eddy", "Rolf"]);
+        assert_eq!(first["red_team_players"].as_array().unwrap().len(), 4);
+
+        // Ratings come from the player_elo entries of that match
+        assert_eq!(first["blue_team_players"][0]["match_elo"], 1215);
+        assert!(first["blue_team_players"][1]["match_elo"].is_null());
+        assert_eq!(first["red_team_players"][0]["match_elo"], 1225);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_match_detail() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Match 102: blue was favoured at 0.52 but red won 2:0
+        let res = request.get("/api/matches/2/detail").await;
+        assert_eq!(res.status_code(), 200);
+        let detail = res.json::<serde_json::Value>();
+        assert_eq!(detail["score"]["blue"], 0);
+        assert_eq!(detail["score"]["red"], 2);
+        assert_eq!(detail["prediction"]["predicted"], "blue");
+        assert_eq!(detail["prediction"]["actual"], "red");
+        assert_eq!(detail["prediction"]["correct"], false);
+
+        let ed = &detail["blue_team"][0];
+        assert_eq!(ed["player_name"], "Ed");
+        assert_eq!(ed["elo_before"], 1215);
+        assert_eq!(ed["elo_after"], 1200);
+        assert_eq!(ed["elo_change"], -15);
+        assert!(detail["blue_team"][1]["elo_change"].is_null());
+
+        let res = request.get("/api/matches/999/detail").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_versus_winrate() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Ed and Kevin faced each other in matches 1 (blue win, 3:1) and 3
+        // (draw); they were teammates in match 2.
+        let res = request.get("/api/matches/versus-winrate/ed/kevin").await;
+        assert_eq!(res.status_code(), 200);
+        let stats = res.json::<serde_json::Value>();
+        assert_eq!(stats["games_played"], 2);
+        assert_eq!(stats["draws"], 1);
+        assert_eq!(stats["player1"]["wins"], 1);
+        assert_eq!(stats["player2"]["wins"], 0);
+        assert_eq!(stats["average_score_margin"], 1.0);
+        // Match 101 is Ed's first rated game, measured from the 1000 start:
+        // +215 there and +20 in match 103
+        assert_eq!(stats["player1"]["elo_change"], 235);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_duo_stats() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Ed and Edd were teammates in match 1 (win) and match 3 (draw).
+        let res = request.get("/api/matches/duo-stats/Ed/Edd").await;
+        assert_eq!(res.status_code(), 200);
+        let stats = res.json::<serde_json::Value>();
+        assert_eq!(stats["games_played"], 2);
+        assert_eq!(stats["wins"], 1);
+        assert_eq!(stats["losses"], 0);
+        assert_eq!(stats["draws"], 1);
+        assert_eq!(stats["by_map"]["well6"]["games_played"], 2);
+        assert_eq!(stats["by_game_type"]["4v4"]["wins"], 1);
+
+        let res = request
+            .get("/api/matches/duo-stats/Ed/Edd")
+            .add_query_param("from", "2024-10-02")
+            .await;
+        let stats = res.json::<serde_json::Value>();
+        assert_eq!(stats["games_played"], 1);
+        assert_eq!(stats["draws"], 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn reporting_a_match_requires_authentication() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({
+            "blue_team": ["100000000000000001"],
+            "red_team": ["200000000000000005"],
+            "match_outcome": 1,
+        });
+        let res = request.post("/api/matches").json(&payload).await;
+        assert_eq!(res.status_code(), 401);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn reporting_a_match_requires_the_bot_role() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({
+            "blue_team": ["100000000000000001"],
+            "red_team": ["200000000000000005"],
+            "match_outcome": 1,
+        });
+        let res = request
+            .post("/api/matches")
+            .add_header(AUTHORIZATION, bearer(VIEWER_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 403);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn bot_can_report_and_correct_a_match() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({
+            "blue_team": ["100000000000000001", "100000000000000002"],
+            "red_team": ["200000000000000005", "200000000000000006"],
+            "match_outcome": 1,
+            "game_type": "2v2",
+            "map": "well6",
+        });
+        let res = request
+            .post("/api/matches")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let reported = res.json::<serde_json::Value>();
+        assert_eq!(reported["match_data"]["match_id"], 104);
+        assert_eq!(reported["elo_entries"].as_array().unwrap().len(), 4);
+
+        let id = reported["match_data"]["id"].as_u64().unwrap();
+        let res = request
+            .patch(&format!("/api/matches/{id}"))
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "match_outcome": 2 }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let corrected = res.json::<serde_json::Value>();
+        assert_eq!(corrected["match_data"]["match_outcome"], 2);
+        assert_eq!(corrected["elo_entries"].as_array().unwrap().len(), 4);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn reporting_a_taken_match_number_is_rejected() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({
+            "match_id": 105,
+            "blue_team": ["100000000000000001"],
+            "red_team": ["200000000000000005"],
+            "match_outcome": 0,
+            "game_type": "1v1",
+            "map": "2fort",
+        });
+        let res = request
+            .post("/api/matches")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&payload)
+            .await;
         assert_eq!(res.status_code(), 200);
-        assert_eq!(res.text(), "hello");
+        let reported = res.json::<serde_json::Value>();
+        assert_eq!(reported["match_data"]["match_id"], 105);
+        assert_eq!(reported["elo_entries"].as_array().unwrap().len(), 2);
+
+        let res = request
+            .post("/api/matches")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 400);
+
+        // Unnumbered reports carry on from the highest number
+        let mut unnumbered = payload.clone();
+        unnumbered.as_object_mut().unwrap().remove("match_id");
+        let res = request
+            .post("/api/matches")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&unnumbered)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["match_data"]["match_id"], 106);
+
+        let res = request
+            .patch("/api/matches/1")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "match_id": 102 }))
+            .await;
+        assert_eq!(res.status_code(), 400);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_upload_stats_log() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let log = include_str!("../../src/fixtures/logs/101.log");
+
+        let res = request.post("/api/matches/1/stats-log").text(log).await;
+        assert_eq!(res.status_code(), 401);
+
+        let res = request
+            .post("/api/matches/1/stats-log")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .text(log)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let summary = res.json::<serde_json::Value>();
+        assert_eq!(summary["imported"], 8);
+        assert_eq!(summary["unmatched"], serde_json::json!(["Guest"]));
+        assert_eq!(summary["map_mismatch"], false);
+
+        let res = request.get("/api/matches/1/player-stats").await;
+        let rows = res.json::<serde_json::Value>();
+        assert_eq!(rows.as_array().unwrap().len(), 8);
+        assert_eq!(rows[0]["team"], "blue");
+
+        let res = request.get("/api/players/edd/match-stats").await;
+        let stats = res.json::<serde_json::Value>();
+        assert_eq!(stats[0]["match_number"], 101);
+        assert_eq!(stats[0]["flag_caps"], 2);
+
+        // The 2fort log doesn't belong to a well6 match
+        let res = request
+            .post("/api/matches/1/stats-log")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .text(include_str!("../../src/fixtures/logs/102.log"))
+            .await;
+        assert_eq!(res.json::<serde_json::Value>()["map_mismatch"], true);
     })
     .await;
 }
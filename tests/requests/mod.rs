@@ -1,3 +1,12 @@
+pub mod achievements;
+pub mod auth;
+pub mod live;
+pub mod calibration;
+pub mod maps;
 pub mod matches;
+pub mod penalties;
 pub mod players;
-pub mod player_elo;
\ No newline at end of file
+pub mod player_elo;
+pub mod seasons;
+pub mod servers;
+pub mod teams;
@@ -0,0 +1,98 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+const VIEWER_API_KEY: &str = "lo-153561ca-fa84-4e1b-813a-c62526d0a77e";
+const ADMIN_API_KEY: &str = "lo-3c0a8f5e-2d7b-4f0e-9a61-5b8e2f9d7c14";
+
+fn bearer(token: &str) -> HeaderValue {
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_active_dunces() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Sarah's penalty ran out and Ed's was revoked
+        let res = request.get("/api/penalties").await;
+        assert_eq!(res.status_code(), 200);
+        let active = res.json::<serde_json::Value>();
+        assert_eq!(active.as_array().unwrap().len(), 1);
+        assert_eq!(active[0]["player_name"], "Jimmy");
+        assert_eq!(active[0]["active"], true);
+
+        let res = request.get("/api/players/name/jimmy").await;
+        assert_eq!(res.json::<serde_json::Value>()["dunced"], true);
+        let res = request.get("/api/players/name/sarah").await;
+        assert_eq!(res.json::<serde_json::Value>()["dunced"], false);
+
+        let res = request.get("/api/players/sarah/penalties").await;
+        let history = res.json::<serde_json::Value>();
+        assert_eq!(history.as_array().unwrap().len(), 1);
+        assert_eq!(history[0]["reason"], "Late to match 101");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn admins_can_issue_and_revoke_penalties() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({
+            "player": "200000000000000007",
+            "reason": "Ragequit",
+            "expires_at": "2099-01-01T00:00:00Z",
+            "match_id": 3,
+        });
+        let res = request.post("/api/penalties").json(&payload).await;
+        assert_eq!(res.status_code(), 401);
+        let res = request
+            .post("/api/penalties")
+            .add_header(AUTHORIZATION, bearer(VIEWER_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 403);
+
+        let res = request
+            .post("/api/penalties")
+            .add_header(AUTHORIZATION, bearer(ADMIN_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let penalty = res.json::<serde_json::Value>();
+        assert_eq!(penalty["player_name"], "Sarah");
+        assert_eq!(penalty["issued_by"], 3);
+        assert_eq!(penalty["active"], true);
+        let res = request.get("/api/players/name/sarah").await;
+        assert_eq!(res.json::<serde_json::Value>()["dunced"], true);
+
+        let id = penalty["id"].as_i64().unwrap();
+        let res = request
+            .post(&format!("/api/penalties/{id}/revoke"))
+            .add_header(AUTHORIZATION, bearer(ADMIN_API_KEY))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["active"], false);
+        let res = request.get("/api/players/name/sarah").await;
+        assert_eq!(res.json::<serde_json::Value>()["dunced"], false);
+
+        let res = request
+            .post(&format!("/api/penalties/{id}/revoke"))
+            .add_header(AUTHORIZATION, bearer(ADMIN_API_KEY))
+            .await;
+        assert_eq!(res.status_code(), 400);
+
+        let res = request
+            .post("/api/penalties")
+            .add_header(AUTHORIZATION, bearer(ADMIN_API_KEY))
+            .json(&serde_json::json!({ "player": "Nobody", "reason": "Ragequit" }))
+            .await;
+        assert_eq!(res.status_code(), 400);
+    })
+    .await;
+}
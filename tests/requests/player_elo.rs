@@ -10,7 +10,7 @@ async fn can_get_echo() {
             "foo": "bar",
         });
 
-        let res = request.post("/player_elo/echo").json(&payload).await;
+        let res = request.post("/api/player_elo/echo").json(&payload).await;
         assert_eq!(res.status_code(), 200);
         assert_eq!(res.text(), serde_json::to_string(&payload).unwrap());
     })
@@ -21,9 +21,68 @@ async fn can_get_echo() {
 #[serial]
 async fn can_request_root() {
     testing::request::<App, _, _>(|request, _ctx| async move {
-        let res = request.get("/player_elo").await;
+        let res = request.get("/api/player_elo").await;
         assert_eq!(res.status_code(), 200);
         assert_eq!(res.text(), "hello");
     })
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_get_elo_history_by_player_name_ignoring_case() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/player_elo/kEVIN").await;
+        assert_eq!(res.status_code(), 200);
+        let history = res.json::<Vec<serde_json::Value>>();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0]["player_elos"], 1225);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_elo_series() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/player_elo/ed/series").await;
+        assert_eq!(res.status_code(), 200);
+        let body = res.json::<serde_json::Value>();
+        assert_eq!(body["bucket"], "match");
+        let points = body["series"][0]["points"].as_array().unwrap();
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[1]["match_id"], 102);
+        assert_eq!(points[1]["close"], 1200);
+
+        // Ed's three entries fall in one ISO week
+        let res = request
+            .get("/api/player_elo/ed,kevin/series")
+            .add_query_param("bucket", "week")
+            .await;
+        let body = res.json::<serde_json::Value>();
+        let ed = &body["series"][0]["points"][0];
+        assert_eq!(ed["start"], "2024-09-30T00:00:00Z");
+        assert_eq!(ed["entries"], 3);
+        assert_eq!(ed["open"], 1215);
+        assert_eq!(ed["high"], 1220);
+        assert_eq!(ed["low"], 1200);
+        assert_eq!(ed["close"], 1220);
+        assert_eq!(body["series"][1]["player_name"], "Kevin");
+
+        let res = request
+            .get("/api/player_elo/ed/series")
+            .add_query_param("bucket", "day")
+            .add_query_param("from", "2024-10-02")
+            .await;
+        let body = res.json::<serde_json::Value>();
+        assert_eq!(body["series"][0]["points"].as_array().unwrap().len(), 2);
+
+        let res = request.get("/api/player_elo/ed,nobody/series").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
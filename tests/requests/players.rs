@@ -1,29 +1,351 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
 use tfpugs_web_app::app::App;
 use loco_rs::testing;
 use serial_test::serial;
 
+const BOT_API_KEY: &str = "lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758";
+const ADMIN_API_KEY: &str = "lo-3c0a8f5e-2d7b-4f0e-9a61-5b8e2f9d7c14";
+
+fn bearer(token: &str) -> HeaderValue {
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_player_by_name_ignoring_case() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/players/name/eDdY").await;
+        assert_eq!(res.status_code(), 200);
+        let player = res.json::<serde_json::Value>();
+        assert_eq!(player["id"], 3);
+        assert_eq!(player["player_name"], "Eddy");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn unknown_player_name_is_not_found() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/players/name/nobody").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
+
 #[tokio::test]
 #[serial]
-async fn can_get_echo() {
-    testing::request::<App, _, _>(|request, _ctx| async move {
-        let payload = serde_json::json!({
-            "foo": "bar",
-        });
+async fn can_get_player_combined_data() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
 
-        let res = request.post("/players/echo").json(&payload).await;
+        let res = request.get("/api/players/combined/ED").await;
         assert_eq!(res.status_code(), 200);
-        assert_eq!(res.text(), serde_json::to_string(&payload).unwrap());
+        let data = res.json::<serde_json::Value>();
+        assert_eq!(data["player"]["player_name"], "Ed");
+
+        let match_ids = data["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(match_ids, vec![3, 2, 1]);
+
+        let elos = data["elo_history"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["player_elos"].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(elos, vec![1215, 1200, 1220]);
     })
     .await;
 }
 
 #[tokio::test]
 #[serial]
-async fn can_request_root() {
-    testing::request::<App, _, _>(|request, _ctx| async move {
-        let res = request.get("/players").await;
+async fn discord_id_prefix_does_not_match_other_players() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Plank's Discord ID is a prefix of Ed's, but Plank never played.
+        let res = request.get("/api/players/combined/plank").await;
         assert_eq!(res.status_code(), 200);
-        assert_eq!(res.text(), "hello");
+        let data = res.json::<serde_json::Value>();
+        assert!(data["matches"].as_array().unwrap().is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_player_stats() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Ed was blue in all three matches: a win, a loss and a draw
+        let res = request.get("/api/players/ed/stats").await;
+        assert_eq!(res.status_code(), 200);
+        let stats = res.json::<serde_json::Value>();
+        assert_eq!(stats["overall"]["games_played"], 3);
+        assert_eq!(stats["by_game_type"]["4v4"]["draws"], 1);
+        assert_eq!(stats["by_map"]["well6"]["wins"], 1);
+        assert_eq!(stats["by_map"]["2fort"]["losses"], 1);
+        assert_eq!(stats["by_team"]["blue"]["games_played"], 3);
+        assert_eq!(stats["by_team"]["red"]["games_played"], 0);
+        assert!(stats["streaks"]["current"].is_null());
+        assert_eq!(stats["streaks"]["longest_win"], 1);
+        assert_eq!(stats["peak_elo"], 1220);
+        assert_eq!(stats["lowest_elo"], 1200);
+        assert_eq!(stats["games_per_week"][0]["week"], "2024-W40");
+        assert_eq!(stats["games_per_week"][0]["games"], 3);
+
+        // The fixture's pug_wins counter has drifted to 2
+        let mismatches = stats["counter_mismatches"].as_array().unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0]["counter"], "pug_wins");
+        assert_eq!(mismatches[0]["stored"], 2);
+        assert_eq!(mismatches[0]["computed"], 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_filter_leaderboard() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/players/by-elo").await;
+        assert_eq!(res.status_code(), 200);
+        let rows = res.json::<serde_json::Value>();
+        assert_eq!(rows.as_array().unwrap().len(), 9);
+        assert_eq!(rows[0]["player_name"], "Kevin");
+        assert_eq!(rows[0]["position"], 1);
+        assert_eq!(rows[0]["rank_tier"], "Gold");
+        assert_eq!(rows[0]["games_played"], 3);
+
+        // Plank never played; Jimmy's tier is overridden
+        let res = request
+            .get("/api/players/by-elo")
+            .add_query_param("min_games", "1")
+            .add_query_param("game_type", "4v4")
+            .await;
+        let rows = res.json::<serde_json::Value>();
+        let rows = rows.as_array().unwrap();
+        assert_eq!(rows.len(), 8);
+        assert!(rows.iter().all(|row| row["player_name"] != "Plank"));
+        assert_eq!(rows[7]["rank_tier"], "Captain");
+
+        let res = request
+            .get("/api/players/by-elo")
+            .add_query_param("active_days", "30")
+            .await;
+        assert!(res.json::<serde_json::Value>().as_array().unwrap().is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn leaderboard_reports_position_change() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Only Kevin (1225) and Ed (1215) had ratings on 2024-10-02
+        let res = request
+            .get("/api/players/by-elo")
+            .add_query_param("since", "2024-10-02")
+            .await;
+        let rows = res.json::<serde_json::Value>();
+        assert_eq!(rows[0]["player_name"], "Kevin");
+        assert_eq!(rows[0]["previous_position"], 1);
+        assert_eq!(rows[0]["position_change"], 0);
+        assert_eq!(rows[1]["player_name"], "Ed");
+        assert_eq!(rows[1]["previous_position"], 2);
+        assert!(rows[2]["position_change"].is_null());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_find_player_by_any_steam_id_format() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        for steam_id in ["76561197960267732", "STEAM_0:0:1002", "STEAM_1:0:1002", "[U:1:2004]"] {
+            let res = request.get(&format!("/api/players/steam/{steam_id}")).await;
+            assert_eq!(res.status_code(), 200, "{steam_id}");
+            assert_eq!(res.json::<serde_json::Value>()["player_name"], "Edd");
+        }
+
+        let res = request.get("/api/players/steam/STEAM_0:1:999999").await;
+        assert_eq!(res.status_code(), 404);
+        let res = request.get("/api/players/steam/not-a-steam-id").await;
+        assert_eq!(res.status_code(), 400);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_link_steam_id() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .put("/api/players/plank/steam")
+            .json(&serde_json::json!({ "steam_id": "[U:1:4242]" }))
+            .await;
+        assert_eq!(res.status_code(), 401);
+
+        // Stored as SteamID64 whatever format it was given in
+        let res = request
+            .put("/api/players/plank/steam")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "steam_id": "[U:1:4242]" }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["steam_id"], "76561197960269970");
+
+        // Edd's account can't be linked to a second player
+        let res = request
+            .put("/api/players/plank/steam")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "steam_id": "STEAM_0:0:1002" }))
+            .await;
+        assert_eq!(res.status_code(), 400);
+
+        let res = request
+            .delete("/api/players/plank/steam")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert!(res.json::<serde_json::Value>()["steam_id"].is_null());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn steam_report_lists_shared_accounts() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/players/steam-report")
+            .add_header(AUTHORIZATION, bearer(ADMIN_API_KEY))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let report = res.json::<serde_json::Value>();
+
+        // Plank holds Ed's account in SteamID2 form
+        let shared = report["shared"].as_array().unwrap();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0]["steam_id"]["steam2"], "STEAM_0:0:1001");
+        assert_eq!(shared[0]["players"][0]["player_name"], "Ed");
+        assert_eq!(shared[0]["players"][1]["player_name"], "Plank");
+        assert_eq!(shared[0]["suggested_primary"], 1);
+        assert_eq!(report["unnormalized"][0]["player_name"], "Plank");
+        assert!(report["invalid"].as_array().unwrap().is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_search_players_by_prefix_and_typo() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/players/search")
+            .add_query_param("q", "ED")
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let hits = res.json::<serde_json::Value>();
+        let names = hits
+            .as_array()
+            .unwrap()
+            .iter()
+            .take(3)
+            .map(|h| h["player_name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Ed", "Edd", "Eddy"]);
+        assert_eq!(hits[0]["match_kind"], "exact");
+        assert_eq!(hits[1]["match_kind"], "prefix");
+
+        let res = request
+            .get("/api/players/search")
+            .add_query_param("q", "kevn")
+            .await;
+        let hits = res.json::<serde_json::Value>();
+        assert_eq!(hits[0]["player_name"], "Kevin");
+        assert_eq!(hits[0]["match_kind"], "fuzzy");
+
+        // Ed went by Edward before
+        let res = request
+            .get("/api/players/search")
+            .add_query_param("q", "edwrd")
+            .add_query_param("limit", "1")
+            .await;
+        let hits = res.json::<serde_json::Value>();
+        assert_eq!(hits.as_array().unwrap().len(), 1);
+        assert_eq!(hits[0]["player_name"], "Ed");
+        assert_eq!(hits[0]["matched_name"], "Edward");
+        assert_eq!(hits[0]["alias"], true);
+
+        let res = request
+            .get("/api/players/search")
+            .add_query_param("q", " ")
+            .await;
+        assert_eq!(res.status_code(), 400);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn old_names_keep_resolving_after_a_rename() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/players/name/jimbo").await;
+        assert_eq!(res.json::<serde_json::Value>()["player_name"], "Jimmy");
+
+        let rename = serde_json::json!({ "player_name": "Eddy Boy" });
+        let res = request.put("/api/players/eddy/name").json(&rename).await;
+        assert_eq!(res.status_code(), 401);
+
+        let res = request
+            .put("/api/players/eddy/name")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&rename)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["player_name"], "Eddy Boy");
+
+        let res = request.get("/api/players/name/eddy").await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["id"], 3);
+
+        let res = request.get("/api/players/eddy/aliases").await;
+        let aliases = res.json::<serde_json::Value>();
+        assert_eq!(aliases[0]["alias"], "Eddy");
+
+        // Another player's current name is taken
+        let res = request
+            .put("/api/players/eddy/name")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "player_name": "ed" }))
+            .await;
+        assert_eq!(res.status_code(), 400);
     })
     .await;
 }
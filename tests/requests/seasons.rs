@@ -0,0 +1,126 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_get_season_leaderboard() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/seasons/season%202/leaderboard").await;
+        assert_eq!(res.status_code(), 200);
+        let leaderboard = res.json::<serde_json::Value>();
+        assert_eq!(leaderboard["archived"], false);
+        assert_eq!(leaderboard["standings"][0]["position"], 1);
+        assert_eq!(leaderboard["standings"][0]["player_name"], "Eddy");
+        assert_eq!(leaderboard["standings"][0]["rating"], 1017);
+
+        let res = request.get("/api/seasons/nothing/leaderboard").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_scope_matches_and_stats_to_a_season() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/matches")
+            .add_query_param("season", "Season 1")
+            .await;
+        let page = res.json::<serde_json::Value>();
+        assert_eq!(page["total"], 1);
+        assert_eq!(page["items"][0]["match_id"], 101);
+
+        let res = request
+            .get("/api/players/ed/stats")
+            .add_query_param("season", "Season 2")
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let stats = res.json::<serde_json::Value>();
+        assert_eq!(stats["overall"]["games_played"], 2);
+        assert_eq!(stats["peak_elo"], 1220);
+        assert_eq!(stats["lowest_elo"], 1200);
+        assert!(stats["counter_mismatches"].as_array().unwrap().is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn only_admins_can_create_seasons() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({
+            "name": "Season 3",
+            "starts_at": "2025-01-01T00:00:00Z",
+        });
+        let res = request
+            .post("/api/seasons")
+            .add_header(
+                AUTHORIZATION,
+                HeaderValue::from_static("Bearer lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758"),
+            )
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 403);
+
+        let res = request
+            .post("/api/seasons")
+            .add_header(
+                AUTHORIZATION,
+                HeaderValue::from_static("Bearer lo-3c0a8f5e-2d7b-4f0e-9a61-5b8e2f9d7c14"),
+            )
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["starting_rating"], 1000);
+
+        let res = request.get("/api/seasons").await;
+        let seasons = res.json::<serde_json::Value>();
+        assert_eq!(seasons.as_array().unwrap().len(), 3);
+        assert_eq!(seasons[0]["name"], "Season 3");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn admins_can_rename_and_reopen_seasons() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let admin = HeaderValue::from_static("Bearer lo-3c0a8f5e-2d7b-4f0e-9a61-5b8e2f9d7c14");
+
+        let res = request
+            .patch("/api/seasons/season%201")
+            .add_header(AUTHORIZATION, admin.clone())
+            .json(&serde_json::json!({ "name": "Season 2" }))
+            .await;
+        assert_eq!(res.status_code(), 400);
+
+        let res = request
+            .patch("/api/seasons/season%201")
+            .add_header(AUTHORIZATION, admin.clone())
+            .json(&serde_json::json!({ "name": "Season One" }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["ends_at"], "2024-10-02T00:00:00Z");
+
+        let res = request
+            .patch("/api/seasons/season%20one")
+            .add_header(AUTHORIZATION, admin)
+            .json(&serde_json::json!({ "ends_at": null }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let season = res.json::<serde_json::Value>();
+        assert_eq!(season["name"], "Season One");
+        assert!(season["ends_at"].is_null());
+    })
+    .await;
+}
@@ -0,0 +1,52 @@
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_list_servers() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request
+            .get("/api/servers")
+            .add_query_param("min_games", "1")
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let servers = res.json::<serde_json::Value>();
+        assert_eq!(servers.as_array().unwrap().len(), 2);
+
+        // East hosted the draw, so its draw rate stands out against West
+        let east = &servers[0];
+        assert_eq!(east["server"], "East");
+        assert_eq!(east["times_played"], 2);
+        assert_eq!(east["last_used"], "2024-10-03T20:00:00Z");
+        assert_eq!(east["maps"][0]["map"], "well6");
+        assert_eq!(east["maps"][0]["games"], 2);
+        assert_eq!(east["flags"], serde_json::json!(["high_draw_rate"]));
+        assert_eq!(servers[1]["flags"], serde_json::json!([]));
+
+        // Too few games to flag by default
+        let res = request.get("/api/servers").await;
+        assert_eq!(res.json::<serde_json::Value>()[0]["flags"], serde_json::json!([]));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_server() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let res = request.get("/api/servers/west").await;
+        assert_eq!(res.status_code(), 200);
+        let server = res.json::<serde_json::Value>();
+        assert_eq!(server["server"], "West");
+        assert_eq!(server["red_wins"], 1);
+
+        let res = request.get("/api/servers/nowhere").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
@@ -0,0 +1,96 @@
+use axum::http::{header::AUTHORIZATION, HeaderValue};
+use tfpugs_web_app::app::App;
+use loco_rs::testing;
+use serial_test::serial;
+
+const BOT_API_KEY: &str = "lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758";
+const VIEWER_API_KEY: &str = "lo-153561ca-fa84-4e1b-813a-c62526d0a77e";
+
+fn bearer(token: &str) -> HeaderValue {
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+fn names(team: &serde_json::Value) -> Vec<&str> {
+    team.as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["player_name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_balance_teams() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        // Rolf by Discord ID, everyone else by name
+        let players = serde_json::json!([
+            "Ed", "Edd", "eddy", "100000000000000004", "Kevin", "Nazz", "Sarah", "Jimmy"
+        ]);
+        let res = request
+            .post("/api/teams/balance")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({ "players": players }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let splits = res.json::<serde_json::Value>();
+        assert_eq!(splits.as_array().unwrap().len(), 3);
+        assert_eq!(names(&splits[0]["blue"]), ["Ed", "Eddy", "Nazz", "Sarah"]);
+        assert_eq!(splits[0]["blue_rank"], 1072.5);
+        assert_eq!(splits[0]["red_rank"], 1075.0);
+        let probability = splits[0]["blue_probability"].as_f64().unwrap();
+        assert!(probability < 0.5 && probability > 0.49);
+
+        let res = request
+            .post("/api/teams/balance")
+            .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+            .json(&serde_json::json!({
+                "players": players,
+                "together": [["Ed", "Edd"]],
+                "captains": ["Ed", "Kevin"],
+                "top_k": 1,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let splits = res.json::<serde_json::Value>();
+        assert_eq!(splits.as_array().unwrap().len(), 1);
+        assert_eq!(names(&splits[0]["blue"]), ["Ed", "Edd", "Rolf", "Jimmy"]);
+        assert_eq!(names(&splits[0]["red"])[1], "Kevin");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn rejects_bad_pickups() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let payload = serde_json::json!({ "players": ["Ed", "Edd"] });
+        let res = request.post("/api/teams/balance").json(&payload).await;
+        assert_eq!(res.status_code(), 401);
+        let res = request
+            .post("/api/teams/balance")
+            .add_header(AUTHORIZATION, bearer(VIEWER_API_KEY))
+            .json(&payload)
+            .await;
+        assert_eq!(res.status_code(), 403);
+
+        for payload in [
+            serde_json::json!({ "players": ["Ed", "Edd", "Eddy"] }),
+            serde_json::json!({ "players": ["Ed", "Nobody"] }),
+            serde_json::json!({ "players": ["Ed", "ed"] }),
+            serde_json::json!({ "players": ["Ed", "Edd"], "apart": [["Ed", "Kevin"]] }),
+            serde_json::json!({ "players": ["Ed", "Edd"], "together": [["Ed", "Edd"]] }),
+        ] {
+            let res = request
+                .post("/api/teams/balance")
+                .add_header(AUTHORIZATION, bearer(BOT_API_KEY))
+                .json(&payload)
+                .await;
+            assert_eq!(res.status_code(), 400, "{payload}");
+        }
+    })
+    .await;
+}
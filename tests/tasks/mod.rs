@@ -0,0 +1 @@
+mod recompute_elo;
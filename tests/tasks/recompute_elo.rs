@@ -0,0 +1,34 @@
+use loco_rs::{boot::run_task, prelude::*, testing};
+use sea_orm::PaginatorTrait;
+use serial_test::serial;
+use tfpugs_web_app::{app::App, models::_entities::player_elo};
+
+fn vars(args: &[(&str, &str)]) -> task::Vars {
+    task::Vars::from_cli_args(
+        args.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect(),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn rebuild_refuses_a_game_type_filter() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let ctx = &boot.app_context;
+    testing::seed::<App>(&ctx.db).await.unwrap();
+    let task = "recompute_elo".to_string();
+    let before = player_elo::Entity::find().count(&ctx.db).await.unwrap();
+    assert!(before > 0);
+
+    // The history of other game types must not be thrown away
+    let filtered = vars(&[("mode", "rebuild"), ("game_type", "4v4")]);
+    assert!(run_task::<App>(ctx, Some(&task), &filtered).await.is_err());
+    assert_eq!(player_elo::Entity::find().count(&ctx.db).await.unwrap(), before);
+
+    // Diffing one game type is read only and still allowed
+    let diff = vars(&[("mode", "diff"), ("game_type", "4v4")]);
+    run_task::<App>(ctx, Some(&task), &diff).await.unwrap();
+    run_task::<App>(ctx, Some(&task), &vars(&[("mode", "rebuild")])).await.unwrap();
+    assert!(player_elo::Entity::find().count(&ctx.db).await.unwrap() > 0);
+}
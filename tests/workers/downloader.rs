@@ -0,0 +1,184 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{
+    http::{header::AUTHORIZATION, HeaderValue, StatusCode},
+    routing::get,
+    Router,
+};
+use loco_rs::{
+    prelude::*,
+    testing,
+    worker::{AppWorker, Worker},
+};
+use serial_test::serial;
+use tfpugs_web_app::{
+    app::App,
+    models::{_entities::matches, match_player_stats, stats_snapshots},
+    stats_log::ParsedLog,
+    workers::downloader::{DownloadWorker, DownloadWorkerArgs},
+};
+
+const BOT_BEARER: &str = "Bearer lo-95ec80d7-cb60-4b70-9b4b-9ef74cb88758";
+const LOG_101: &str = include_str!("../../src/fixtures/logs/101.log");
+
+/// Serves `router` on a free local port, standing in for a stats server.
+async fn stand_in(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn set_stats_url(db: &DatabaseConnection, id: i32, url: String) {
+    let mut item = matches::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+        .into_active_model();
+    item.stats_url = Set(Some(url));
+    item.update(db).await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn a_busy_server_fails_the_job_for_the_queue_to_retry() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let ctx = &boot.app_context;
+    testing::seed::<App>(&ctx.db).await.unwrap();
+
+    // The stand-in is busy on the first try
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let base = stand_in(Router::new().route(
+        "/logs/101.log",
+        get(move || async move {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                Ok(LOG_101)
+            }
+        }),
+    ))
+    .await;
+    set_stats_url(&ctx.db, 1, format!("{base}/logs/101.log")).await;
+
+    let worker = DownloadWorker::build(ctx);
+    assert!(worker.perform(DownloadWorkerArgs { match_id: 1 }).await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    let snapshot = stats_snapshots::for_match(&ctx.db, 1).await.unwrap().unwrap();
+    assert_eq!(snapshot.status, stats_snapshots::FAILED);
+    assert_eq!(snapshot.attempts, 1);
+    assert_eq!(snapshot.http_status, Some(503));
+
+    // The queue's retry
+    worker.perform(DownloadWorkerArgs { match_id: 1 }).await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    let snapshot = stats_snapshots::for_match(&ctx.db, 1).await.unwrap().unwrap();
+    assert_eq!(snapshot.status, stats_snapshots::FETCHED);
+    assert_eq!(snapshot.attempts, 2);
+    assert_eq!(snapshot.http_status, Some(200));
+    assert!(snapshot.error.is_none());
+    assert_eq!(snapshot.body.as_deref(), Some(LOG_101));
+    let summary = snapshot.summary.unwrap();
+    assert_eq!(summary["map"], "well6");
+    assert_eq!(summary["import"]["imported"], 8);
+
+    let stats = match_player_stats::for_match(&ctx.db, 1).await.unwrap();
+    assert_eq!(stats.len(), 8);
+    assert!(stats.iter().all(|row| row.source == match_player_stats::DOWNLOADED));
+}
+
+#[tokio::test]
+#[serial]
+async fn keeps_stats_uploaded_by_hand() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let ctx = &boot.app_context;
+    testing::seed::<App>(&ctx.db).await.unwrap();
+
+    let match_item = matches::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap();
+    let mut uploaded = ParsedLog::parse(LOG_101);
+    uploaded.players.truncate(3);
+    match_player_stats::import(&ctx.db, &match_item, &uploaded, match_player_stats::UPLOADED)
+        .await
+        .unwrap();
+    let before = match_player_stats::for_match(&ctx.db, 1).await.unwrap();
+    assert!(!before.is_empty());
+
+    let base = stand_in(Router::new().route("/logs/101.log", get(|| async { LOG_101 }))).await;
+    set_stats_url(&ctx.db, 1, format!("{base}/logs/101.log")).await;
+    DownloadWorker::build(ctx)
+        .perform(DownloadWorkerArgs { match_id: 1 })
+        .await
+        .unwrap();
+
+    let snapshot = stats_snapshots::for_match(&ctx.db, 1).await.unwrap().unwrap();
+    assert_eq!(snapshot.status, stats_snapshots::FETCHED);
+    let summary = snapshot.summary.unwrap();
+    assert_eq!(summary["kept_upload"], true);
+    assert!(summary["import"].is_null());
+    assert_eq!(match_player_stats::for_match(&ctx.db, 1).await.unwrap(), before);
+}
+
+#[tokio::test]
+#[serial]
+async fn records_a_download_that_cannot_succeed() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let ctx = &boot.app_context;
+    testing::seed::<App>(&ctx.db).await.unwrap();
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let base = stand_in(Router::new().route(
+        "/logs/gone.log",
+        get(move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            StatusCode::NOT_FOUND
+        }),
+    ))
+    .await;
+    set_stats_url(&ctx.db, 2, format!("{base}/logs/gone.log")).await;
+
+    DownloadWorker::build(ctx)
+        .perform(DownloadWorkerArgs { match_id: 2 })
+        .await
+        .unwrap();
+
+    // A 404 is not retried
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    let snapshot = stats_snapshots::for_match(&ctx.db, 2).await.unwrap().unwrap();
+    assert_eq!(snapshot.status, stats_snapshots::FAILED);
+    assert_eq!(snapshot.http_status, Some(404));
+    assert!(snapshot.body.is_none());
+    assert!(snapshot.error.is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn setting_a_stats_url_queues_a_download() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let base = stand_in(Router::new().route("/logs/101.log", get(|| async { LOG_101 }))).await;
+
+        let res = request.get("/api/matches/1/stats-snapshot").await;
+        assert_eq!(res.status_code(), 404);
+
+        // Workers run in the foreground under the test config
+        let res = request
+            .patch("/api/matches/1")
+            .add_header(AUTHORIZATION, HeaderValue::from_static(BOT_BEARER))
+            .json(&serde_json::json!({ "stats_url": format!("{base}/logs/101.log") }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        let res = request.get("/api/matches/1/stats-snapshot").await;
+        assert_eq!(res.status_code(), 200);
+        let snapshot = res.json::<serde_json::Value>();
+        assert_eq!(snapshot["status"], "fetched");
+        assert_eq!(snapshot["summary"]["import"]["unmatched"], serde_json::json!(["Guest"]));
+    })
+    .await;
+}